@@ -0,0 +1,24 @@
+use std::io::BufReader;
+
+fn main() -> Result<(), std::io::Error> {
+    env_logger::init();
+
+    let file = std::env::args()
+        .nth(1)
+        .expect("Please provide a demo file path as the first argument");
+    let file = std::fs::File::open(file).expect("Failed to open demo file");
+
+    let mut parser =
+        demoinfocs2_lite::CsDemoParser::new(BufReader::with_capacity(1024 * 128, file))?;
+
+    // send tables are part of the signon frames at the start of the demo
+    while parser.class_schemas().is_empty() {
+        if !parser.read_frame()? {
+            break;
+        }
+    }
+
+    print!("{}", parser.schema_json());
+
+    Ok(())
+}
@@ -1,5 +1,8 @@
 use demoinfocs2_lite::{
-    CsDemoParserState, entity::EntityClass, event::DemoStartEvent, game_event::derive::GameEvent,
+    CsDemoParserState,
+    entity::{EntityClass, serializer::PolymorphicEntity},
+    event::DemoStartEvent,
+    game_event::derive::GameEvent,
 };
 use std::io::BufReader;
 
@@ -103,7 +106,7 @@ pub struct CCSGameRulesProxy {
 #[entity(crate_path = demoinfocs2_lite)]
 pub struct CCSGameRules {
     #[entity(name = "m_pGameModeRules")]
-    pub game_mode_rules: usize,
+    pub game_mode_rules: PolymorphicEntity,
 }
 
 impl CCSPlayerController {
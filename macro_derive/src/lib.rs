@@ -3,7 +3,10 @@ use proc_macro::TokenStream;
 use proc_macro_crate::{FoundCrate, crate_name};
 use proc_macro2::{Ident, Literal};
 use quote::quote;
-use syn::{Attribute, ItemStruct, LitStr, Path, Result, parse_macro_input};
+use syn::{
+    Attribute, GenericArgument, ItemStruct, LitStr, Path, PathArguments, Result, Type,
+    parse_macro_input,
+};
 
 fn parse_game_event_attr(attrs: &[Attribute], attr_name: &str) -> Result<Option<Path>> {
     for attr in attrs {
@@ -86,8 +89,7 @@ pub fn game_event_derive(input: TokenStream) -> TokenStream {
             #field_name: keys.iter()
                 .find(|(_, name)| name == #field_name_lit)
                 .map(|(value, _)| *value)
-                .ok_or_else(|| std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
+                .ok_or_else(|| #crate_path::error::ParserError::invalid_data(
                     format!("Missing field: {}", #field_name_lit)
                 ))?,
         })
@@ -123,8 +125,7 @@ pub fn game_event_derive(input: TokenStream) -> TokenStream {
         quote! {
             if i as u32 == self.#field_name {
                 v.#field_name = k.#type_field.ok_or_else(|| {
-                    std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
+                    #crate_path::error::ParserError::invalid_data(
                         format!("Missing or wrong type for key: {}", stringify!(#field_name)),
                     )
                 })?;
@@ -133,14 +134,18 @@ pub fn game_event_derive(input: TokenStream) -> TokenStream {
     });
 
     quote! {
-        impl #crate_path::event::Event for #ident_msg {}
+        impl #crate_path::event::Event for #ident_msg {
+            fn into_demo_event(self) -> Option<#crate_path::event::DemoEvent> {
+                Some(#crate_path::event::DemoEvent::GameEvent(Box::new(self)))
+            }
+        }
 
         struct #ident_eid {
             #( #fields )*
         }
 
         impl #ident_msg {
-            fn factory(keys: &#crate_path::game_event::derive::ListKeysT) -> Result<Box<dyn #crate_path::game_event::derive::GameEventSerializer>, std::io::Error> {
+            fn factory(keys: &#crate_path::game_event::derive::ListKeysT) -> Result<Box<dyn #crate_path::game_event::derive::GameEventSerializer>, #crate_path::error::ParserError> {
                 Ok(Box::new(#ident_eid {
                     #( #factory )*
                 }))
@@ -148,7 +153,7 @@ pub fn game_event_derive(input: TokenStream) -> TokenStream {
         }
 
         impl #crate_path::game_event::derive::GameEventSerializer for #ident_eid {
-            fn parse_and_dispatch_event(&self, keys: Vec<#crate_path::game_event::derive::KeyT>, event_manager: &mut #crate_path::event::EventManager, state: &#crate_path::CsDemoParserState) -> Result<(), std::io::Error> {
+            fn parse_and_dispatch_event(&self, keys: Vec<#crate_path::game_event::derive::KeyT>, event_manager: &mut #crate_path::event::EventManager, state: &#crate_path::CsDemoParserState) -> Result<(), #crate_path::error::ParserError> {
                 let mut v = #ident_msg::default();
 
                 for (i, mut k) in keys.into_iter().enumerate() {
@@ -181,83 +186,285 @@ pub fn entity_class_derive(input: TokenStream) -> TokenStream {
     };
 
     let ident = &ast.ident;
+    let serializer_path = quote! { #crate_path::entity::serializer };
 
-    let fields = ast
-        .fields
-        .iter()
-        .filter_map(|f| {
-            let field_ident = f.ident.as_ref()?;
-            let field_ident_str = field_ident.to_string();
-            let field_ident_str = field_ident_str.trim_start_matches("r#");
+    let mut fields = Vec::new();
+    let mut flattened = Vec::new();
+    for f in ast.fields.iter() {
+        let Some(field_ident) = f.ident.as_ref() else {
+            continue;
+        };
 
-            let getter_ident = Ident::new(&format!("{field_ident_str}_get"), field_ident.span());
-            let (attr_name, on_changed) = get_entity_attr_name(&f.attrs);
-            let attr_name = attr_name?;
+        match get_entity_attr(&f.attrs) {
+            Some(EntityAttr::Bind {
+                name,
+                on_changed,
+                as_enum,
+            }) => {
+                let field_ident_str = field_ident.to_string();
+                let field_ident_str = field_ident_str.trim_start_matches("r#");
+
+                let getter_ident = Ident::new(
+                    &format!("__entity_{field_ident_str}_get"),
+                    field_ident.span(),
+                );
+                let on_changed_ident = Ident::new(
+                    &format!("__entity_{field_ident_str}_on_changed"),
+                    field_ident.span(),
+                );
+                let from_int_ident = Ident::new(
+                    &format!("__entity_{field_ident_str}_from_int"),
+                    field_ident.span(),
+                );
+
+                let from_int = if as_enum {
+                    Some(IntConversion::Enum)
+                } else if is_int_convertible(&f.ty) {
+                    Some(IntConversion::Scalar)
+                } else {
+                    vec_element(&f.ty)
+                        .filter(|ty| is_int_convertible(ty))
+                        .map(IntConversion::Vec)
+                };
+
+                fields.push(EntityField {
+                    field_ident,
+                    field_ty: &f.ty,
+                    getter_ident,
+                    on_changed_ident,
+                    from_int_ident,
+                    name,
+                    on_changed,
+                    from_int,
+                });
+            }
+            Some(EntityAttr::Flatten) => flattened.push((field_ident, &f.ty)),
+            None => {}
+        }
+    }
 
-            Some((field_ident, getter_ident, attr_name, on_changed))
-        })
-        .collect::<Box<_>>();
+    // getters are generic over the accessor so flattened classes can reuse them
+    let getters = fields.iter().map(|field| {
+        let EntityField {
+            field_ident,
+            field_ty,
+            getter_ident,
+            on_changed_ident,
+            from_int_ident,
+            ..
+        } = field;
+
+        let on_changed = field.on_changed.as_ref().map(|on_changed| {
+            quote! {
+                #[doc(hidden)]
+                fn #on_changed_ident<A: #serializer_path::FieldAccessor<Inner = Self>>(
+                    e: &mut A::Outer,
+                ) -> Result<(), std::io::Error> {
+                    #on_changed(A::get(e))
+                }
+            }
+        });
+
+        // unknown values fall back to the default variant
+        let from_int = matches!(field.from_int, Some(IntConversion::Enum)).then(|| {
+            quote! {
+                #[doc(hidden)]
+                fn #from_int_ident(value: i128, field: &mut dyn std::any::Any) {
+                    if let Some(field) = field.downcast_mut::<#field_ty>() {
+                        *field = u64::try_from(value)
+                            .ok()
+                            .and_then(|v| <#field_ty as TryFrom<u64>>::try_from(v).ok())
+                            .unwrap_or_default();
+                    }
+                }
+            }
+        });
 
-    let getters = fields.iter().map(|(field_ident, getter_ident, _, _)| {
         quote! {
-            fn #getter_ident(e: &mut #ident) -> &mut dyn std::any::Any {
-                &mut e.#field_ident
+            #[doc(hidden)]
+            fn #getter_ident<A: #serializer_path::FieldAccessor<Inner = Self>>(
+                e: &mut A::Outer,
+            ) -> &mut dyn std::any::Any {
+                &mut A::get(e).#field_ident
             }
+
+            #on_changed
+            #from_int
         }
     });
 
-    let match_branches = fields
-        .iter()
-        .map(|(_, getter_ident, attr_name, on_changed)| {
-            // TODO: perfect hash
-            if let Some(on_changed) = on_changed {
-                quote! {
-                    #attr_name => (Some(#getter_ident as fn(&mut #ident) -> &mut dyn std::any::Any), Some(#on_changed as fn(&mut #ident) -> Result<(), std::io::Error>)),
-                }
-            } else {
-                quote! {
-                    #attr_name => (Some(#getter_ident as fn(&mut #ident) -> &mut dyn std::any::Any), None),
+    let bindings = fields.iter().map(|field| {
+        let EntityField {
+            field_ty,
+            getter_ident,
+            on_changed_ident,
+            from_int_ident,
+            name,
+            ..
+        } = field;
+
+        let on_changed = if field.on_changed.is_some() {
+            quote! { Some(Self::#on_changed_ident::<A>) }
+        } else {
+            quote! { None }
+        };
+        let from_int = match field.from_int {
+            Some(IntConversion::Enum) => quote! {
+                Some(#serializer_path::IntConversion::Scalar(Self::#from_int_ident))
+            },
+            Some(IntConversion::Scalar) => quote! {
+                Some(#serializer_path::IntConversion::Scalar(
+                    #serializer_path::convert_int::<#field_ty>,
+                ))
+            },
+            Some(IntConversion::Vec(element_ty)) => quote! {
+                Some(#serializer_path::IntConversion::Vec(
+                    #serializer_path::convert_int_vec::<#element_ty>,
+                ))
+            },
+            None => quote! { None },
+        };
+
+        quote! {
+            #serializer_path::FieldBinding {
+                name: #name,
+                type_name: std::any::type_name::<#field_ty>(),
+                getter: Self::#getter_ident::<A>,
+                on_changed: #on_changed,
+                from_int: #from_int,
+            },
+        }
+    });
+
+    let flattened_bindings = flattened.iter().map(|(field_ident, field_ty)| {
+        quote! {
+            {
+                struct Accessor;
+
+                impl #serializer_path::FieldAccessor for Accessor {
+                    type Outer = #ident;
+                    type Inner = #field_ty;
+
+                    fn get(outer: &mut #ident) -> &mut #field_ty {
+                        &mut outer.#field_ident
+                    }
                 }
+
+                bindings.extend(
+                    <#field_ty as #serializer_path::EntityClassBindings>::bindings::<(A, Accessor)>(),
+                );
             }
-        });
+        }
+    });
 
     quote! {
-        impl #crate_path::entity::serializer::EntityField for #ident {
+        impl #serializer_path::EntityField for #ident {
             fn new() -> Self {
                 Self::default()
             }
         }
 
+        impl #serializer_path::EntityClassBindings for #ident {
+            fn bindings<A: #serializer_path::FieldAccessor<Inner = Self>>(
+            ) -> Vec<#serializer_path::FieldBinding<A::Outer>> {
+                #[allow(unused_mut)]
+                let mut bindings = vec![
+                    #( #bindings )*
+                ];
+
+                #( #flattened_bindings )*
+
+                bindings
+            }
+        }
+
         impl #ident {
+            #( #getters )*
+
             pub fn new_serializer(
-                serializers: Vec<(&str, std::sync::Arc<dyn #crate_path::entity::serializer::EntitySerializer>)>,
-            ) -> std::sync::Arc<dyn #crate_path::entity::serializer::EntityClassSerializer> {
-                #( #getters )*
-
-                let new_serializers = serializers
-                    .into_iter()
-                    .map(|(n, s)| {
-                        let (getter, callback) = match n {
-                            #( #match_branches )*
-                            _ => (None, None),
-                        };
-
-                        (
-                            s,
-                            getter,
-                            callback,
-                        )
-                    })
-                    .collect();
-
-                std::sync::Arc::new(#crate_path::entity::serializer::CustomEntitySerializer::<Self>::new(new_serializers))
+                serializers: Vec<(&str, std::sync::Arc<dyn #serializer_path::EntitySerializer>)>,
+            ) -> std::sync::Arc<dyn #serializer_path::EntityClassSerializer> {
+                let bindings = <Self as #serializer_path::EntityClassBindings>::bindings::<
+                    #serializer_path::IdentityAccessor<Self>,
+                >();
+
+                std::sync::Arc::new(#serializer_path::CustomEntitySerializer::<Self>::from_bindings(serializers, &bindings))
             }
         }
     }
     .into()
 }
 
-fn get_entity_attr_name(attrs: &[Attribute]) -> (Option<String>, Option<Path>) {
+struct EntityField<'a> {
+    field_ident: &'a Ident,
+    field_ty: &'a Type,
+    getter_ident: Ident,
+    on_changed_ident: Ident,
+    from_int_ident: Ident,
+    name: String,
+    on_changed: Option<Path>,
+    from_int: Option<IntConversion<'a>>,
+}
+
+/// how a decoded integer is stored into the field
+enum IntConversion<'a> {
+    /// `TryFrom<u64>`, falls back to `Default`
+    Enum,
+    /// `FromEntityInt`
+    Scalar,
+    /// `Vec` of `FromEntityInt`
+    Vec(&'a Type),
+}
+
+/// narrowed integers and entity handles, see `FromEntityInt`
+fn is_int_convertible(ty: &Type) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+
+    if path.qself.is_some() {
+        return false;
+    }
+
+    ["u8", "u16", "u32", "i8", "i16", "i32"]
+        .iter()
+        .any(|int| path.path.is_ident(int))
+        || path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "EntityHandle")
+}
+
+fn vec_element(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Vec" {
+        return None;
+    }
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+enum EntityAttr {
+    Bind {
+        name: String,
+        on_changed: Option<Path>,
+        as_enum: bool,
+    },
+    Flatten,
+}
+
+fn get_entity_attr(attrs: &[Attribute]) -> Option<EntityAttr> {
     for attr in attrs {
         if !attr.path().is_ident("entity") {
             continue;
@@ -265,6 +472,8 @@ fn get_entity_attr_name(attrs: &[Attribute]) -> (Option<String>, Option<Path>) {
 
         let mut name: Option<String> = None;
         let mut on_changed: Option<Path> = None;
+        let mut flatten = false;
+        let mut as_enum = false;
 
         attr.parse_nested_meta(|meta| {
             if meta.path.is_ident("name") {
@@ -275,14 +484,32 @@ fn get_entity_attr_name(attrs: &[Attribute]) -> (Option<String>, Option<Path>) {
                 let value: Path = meta.value()?.parse()?;
                 on_changed = Some(value);
                 Ok(())
+            } else if meta.path.is_ident("flatten") {
+                flatten = true;
+                Ok(())
+            } else if meta.path.is_ident("as_enum") {
+                as_enum = true;
+                Ok(())
             } else {
                 Err(meta.error("unsupported key for #[entity]"))
             }
         })
         .unwrap();
 
-        return (Some(name.unwrap()), on_changed);
+        if flatten {
+            assert!(
+                name.is_none() && on_changed.is_none() && !as_enum,
+                "#[entity(flatten)] can't be combined with other keys"
+            );
+            return Some(EntityAttr::Flatten);
+        }
+
+        return Some(EntityAttr::Bind {
+            name: name.unwrap(),
+            on_changed,
+            as_enum,
+        });
     }
 
-    (None, None)
+    None
 }
@@ -1,7 +1,10 @@
+pub mod codegen;
 pub mod decoder;
 pub mod field;
 pub mod fieldpath;
 pub mod list;
+pub mod position;
+pub mod schema;
 pub mod serializer;
 
 pub use macro_derive::EntityClass;
@@ -21,11 +24,15 @@ use crate::{
         field::FieldType,
         fieldpath::read_field_paths,
         list::EntityItem,
+        schema::{ClassSchema, FieldSchema},
         serializer::{
-            EntityClassSerializer, EntitySerializer, PolymorphicSerializer, UnknownEntity,
-            UnknownEntitySerializer,
+            BindingError, EntityClassSerializer, EntitySerializer, PolymorphicSerializer,
+            UnknownEntity, UnknownEntitySerializer,
         },
     },
+    error::{ErrorContext, ParserError},
+    event::{EntityCreatedEvent, EntityDeletedEvent, EntityUpdatedEvent},
+    game_state::{GAME_STATE_CLASSES, game_state_serializer},
     protobuf::{self},
 };
 
@@ -34,6 +41,69 @@ pub type EntitySerializerCreator =
 
 type Reader<'a> = BitReader<Cursor<&'a [u8]>, bitstream_io::LittleEndian>;
 
+/// selects the classes a creator is registered for,
+/// see `CsDemoParser::register_entity_serializer_matching`
+pub enum ClassMatcher {
+    Prefix(String),
+    Regex(regex::Regex),
+    Predicate(Box<dyn Fn(&str) -> bool + Send + Sync>),
+}
+
+impl ClassMatcher {
+    pub fn prefix(prefix: impl Into<String>) -> Self {
+        ClassMatcher::Prefix(prefix.into())
+    }
+
+    pub fn regex(pattern: &str) -> Result<Self, ParserError> {
+        regex::Regex::new(pattern)
+            .map(ClassMatcher::Regex)
+            .map_err(|err| ParserError::InvalidInput(err.to_string()))
+    }
+
+    pub fn predicate(predicate: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        ClassMatcher::Predicate(Box::new(predicate))
+    }
+
+    pub fn matches(&self, class_name: &str) -> bool {
+        match self {
+            ClassMatcher::Prefix(prefix) => class_name.starts_with(prefix.as_str()),
+            ClassMatcher::Regex(regex) => regex.is_match(class_name),
+            ClassMatcher::Predicate(predicate) => predicate(class_name),
+        }
+    }
+}
+
+impl std::fmt::Display for ClassMatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClassMatcher::Prefix(prefix) => write!(f, "prefix {prefix}"),
+            ClassMatcher::Regex(regex) => write!(f, "regex {}", regex.as_str()),
+            ClassMatcher::Predicate(_) => write!(f, "predicate"),
+        }
+    }
+}
+
+pub(crate) struct ClassSerializer {
+    serializer: Arc<dyn EntityClassSerializer>,
+    serialize_baseline: bool,
+    /// created by an explicitly registered creator
+    registered: bool,
+    schema: Arc<ClassSchema>,
+}
+
+/// wraps an error from an entity serializer with the name of the field
+fn field_decode_error(schema: &ClassSchema, path: &[u32], source: std::io::Error) -> ParserError {
+    ParserError::FieldDecode {
+        context: ErrorContext {
+            entity_class: Some(schema.name.clone()),
+            field: schema.resolve_path(path).map(Arc::from),
+            ..Default::default()
+        },
+        path: path.to_vec(),
+        source,
+    }
+}
+
 impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
     pub fn register_entity_serializer(
         &mut self,
@@ -48,17 +118,95 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
         self.entity_serializer_creators.insert(name, creator);
     }
 
+    /// registers a creator for every class the matcher accepts,
+    /// e.g. one `Weapon` struct for all weapon classes with the same layout
+    ///
+    /// exact registrations take precedence, matchers are tried in registration order,
+    /// see `CsDemoParser::enable_game_state` for the classes of the game state
+    pub fn register_entity_serializer_matching(
+        &mut self,
+        matcher: ClassMatcher,
+        creator: EntitySerializerCreator,
+    ) {
+        if !self.is_fresh() {
+            warn!("Cannot register entity serializer after parsing has started");
+            return;
+        }
+
+        self.entity_serializer_matchers.push((matcher, creator));
+    }
+
+    /// sets the serializer for all classes which are not registered explicitly,
+    /// e.g. `DynamicEntitySerializer::new_serializer` to decode every entity dynamically
+    ///
+    /// this decodes every field of every entity and is considerably slower
+    pub fn set_default_entity_serializer(&mut self, creator: EntitySerializerCreator) {
+        if !self.is_fresh() {
+            warn!("Cannot register entity serializer after parsing has started");
+            return;
+        }
+
+        self.default_entity_serializer_creator = Some(creator);
+    }
+
+    /// schema of the serializer with the given name,
+    /// available once the send tables have been parsed
+    pub fn class_schema(&self, name: &str) -> Option<&Arc<ClassSchema>> {
+        self.entity_serializers.get(name).map(|class| &class.schema)
+    }
+
+    /// schemas of all serializers sorted by name,
+    /// empty until the send tables have been parsed
+    pub fn class_schemas(&self) -> Vec<&Arc<ClassSchema>> {
+        let mut schemas = self
+            .entity_serializers
+            .values()
+            .map(|class| &class.schema)
+            .collect::<Vec<_>>();
+        schemas.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+        schemas
+    }
+
+    /// dumps the schemas of all serializers as JSON, see `schema::to_json`
+    pub fn schema_json(&self) -> String {
+        schema::to_json(self.class_schemas().into_iter().map(|s| s.as_ref()))
+    }
+
+    /// keeps a copy of every updated entity before the update is applied,
+    /// see `EntityUpdatedEvent::previous`
+    ///
+    /// this clones the entity on every update and should only be enabled if needed
+    pub fn set_track_previous_entity_values(&mut self, enabled: bool) {
+        self.track_previous_entity_values = enabled;
+    }
+
+    /// returns an error when the send tables are parsed
+    /// if a registered class doesn't match them, see `binding_errors`
+    pub fn set_strict_entity_bindings(&mut self, enabled: bool) {
+        self.strict_entity_bindings = enabled;
+    }
+
+    /// registered classes and fields missing from the send tables,
+    /// and fields with a type the serializer can't decode into
+    ///
+    /// mismatched fields are not decoded and keep their default value,
+    /// available once the send tables have been parsed
+    pub fn binding_errors(&self) -> &[(String, BindingError)] {
+        &self.binding_errors
+    }
+
     #[cold]
     pub(super) fn handle_demo_class_info(
         &mut self,
         msg: protobuf::CDemoClassInfo,
-    ) -> Result<(), std::io::Error> {
+    ) -> Result<(), ParserError> {
         self.class_info = msg
             .classes
             .into_iter()
             .filter_map(|class| {
                 if let (Some(class_id), Some(class_name)) = (class.class_id, class.network_name) {
-                    Some((class_id as u32, class_name))
+                    Some((class_id as u32, Arc::from(class_name)))
                 } else {
                     error!("Missing class ID or name in class info");
                     None
@@ -73,10 +221,9 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
     pub(super) fn handle_demo_send_tables(
         &mut self,
         msg: protobuf::CDemoSendTables,
-    ) -> Result<(), std::io::Error> {
+    ) -> Result<(), ParserError> {
         if !self.entity_serializers.is_empty() {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
+            return Err(ParserError::invalid_data(
                 "Demo send tables already processed",
             ));
         }
@@ -86,8 +233,7 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
         }
 
         let Some(data) = msg.data else {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
+            return Err(ParserError::invalid_data(
                 "Missing data in demo send tables",
             ));
         };
@@ -99,16 +245,11 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
             r.position_in_bits()? as usize >> 3
         };
 
-        let msg =
-            protobuf::CsvcMsgFlattenedSerializer::decode(data.slice(offset..)).map_err(|err| {
-                std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    format!("Failed to decode flattened serializer: {err:?}"),
-                )
-            })?;
+        let msg = protobuf::CsvcMsgFlattenedSerializer::decode(data.slice(offset..))?;
 
         let mut serializers = HashMap::with_capacity(msg.serializers.len());
-        let mut fields_cache: Vec<Option<(&str, Arc<dyn EntitySerializer>)>> =
+        #[allow(clippy::type_complexity)]
+        let mut fields_cache: Vec<Option<(&str, Arc<dyn EntitySerializer>, FieldSchema)>> =
             vec![None; msg.fields.len()];
         let mut field_type_cache = HashMap::with_capacity(256);
 
@@ -117,43 +258,34 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
                 .serializer_name_sym
                 .and_then(|sym| msg.symbols.get(sym as usize).cloned())
             else {
-                return Err(std::io::Error::other(
+                return Err(ParserError::invalid_data(
                     "Missing serializer name in serializer",
                 ));
             };
 
+            let serializer_version = serializer_pb.serializer_version;
             let mut serializer_fields = Vec::with_capacity(serializer_pb.fields_index.len());
+            let mut schema_fields = Vec::with_capacity(serializer_pb.fields_index.len());
 
             for field_idx in serializer_pb.fields_index {
-                if let Some(field_serializer) = fields_cache
+                if let Some((var_name, field_serializer, field_schema)) = fields_cache
                     .get(field_idx as usize)
                     .and_then(|s| s.as_ref())
                 {
-                    serializer_fields.push(field_serializer.clone());
+                    serializer_fields.push((*var_name, field_serializer.clone()));
+                    schema_fields.push(field_schema.clone());
                     continue;
                 }
 
                 let Some(field_pb) = msg.fields.get(field_idx as usize) else {
-                    return Err(std::io::Error::other("Missing field in serializer"));
+                    return Err(ParserError::invalid_data("Missing field in serializer"));
                 };
 
                 let Some(var_type) = field_pb
                     .var_type_sym
                     .and_then(|sym| msg.symbols.get(sym as usize))
                 else {
-                    return Err(std::io::Error::other("Missing variable type in field"));
-                };
-
-                let var_type = var_type.as_str();
-                let field_type = if let Some(field_type) = field_type_cache.get(var_type) {
-                    field_type
-                } else {
-                    let field_type = FieldType::new(var_type)?;
-                    field_type_cache.insert(var_type, field_type);
-
-                    field_type_cache
-                        .get(var_type)
-                        .expect("Field type should be cached")
+                    return Err(ParserError::invalid_data("Missing variable type in field"));
                 };
 
                 let Some(var_name) = field_pb
@@ -161,7 +293,7 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
                     .and_then(|sym| msg.symbols.get(sym as usize))
                     .map(|s| s.as_str())
                 else {
-                    return Err(std::io::Error::other("Missing variable name in field"));
+                    return Err(ParserError::invalid_data("Missing variable name in field"));
                 };
 
                 let encoder = field_pb
@@ -169,7 +301,36 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
                     .and_then(|sym| msg.symbols.get(sym as usize))
                     .map(|s| s.as_str());
 
+                let context = || ErrorContext {
+                    entity_class: Some(Arc::from(serializer_name.as_str())),
+                    field: Some(Arc::from(var_name)),
+                    ..Default::default()
+                };
+                let unsupported = |err: std::io::Error| ParserError::UnsupportedEncoder {
+                    context: context(),
+                    var_type: var_type.clone(),
+                    encoder: encoder.map(str::to_string),
+                    source: err,
+                };
+
+                let var_type = var_type.as_str();
+                let field_type = if let Some(field_type) = field_type_cache.get(var_type) {
+                    field_type
+                } else {
+                    let field_type = FieldType::new(var_type).map_err(unsupported)?;
+                    field_type_cache.insert(var_type, field_type);
+
+                    field_type_cache
+                        .get(var_type)
+                        .expect("Field type should be cached")
+                };
+
+                let mut nested_schema = None;
                 let serializer = if !field_pb.polymorphic_types.is_empty() {
+                    // the active type is decoded in place of the pointee
+                    let mut pointee_type = field_type.clone();
+                    pointee_type.is_optional = false;
+
                     let polymorphic_serializers = field_pb
                         .polymorphic_types
                         .iter()
@@ -179,20 +340,20 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
                                 .get(sym as usize)
                                 .cloned()
                                 .ok_or_else(|| {
-                                    std::io::Error::other("Missing polymorphic serializer")
+                                    ParserError::invalid_data("Missing polymorphic serializer")
                                 })
                                 .and_then(|s| {
                                     serializers
                                         .get(s.as_str())
-                                        .cloned()
-                                        .map(|(s, _)| s)
-                                        .ok_or_else(|| {
-                                            std::io::Error::other("Missing polymorphic serializer")
+                                        .map(|class: &ClassSerializer| {
+                                            (class.schema.name.clone(), class.serializer.clone())
+                                        })
+                                        .ok_or_else(|| ParserError::UnknownSerializer {
+                                            context: context(),
+                                            class_name: s,
                                         })
                                 })
-                                .map(|s: Arc<dyn EntityClassSerializer>| {
-                                    s.serializer_derivation(field_type)
-                                })
+                                .map(|(name, s)| (name, s.serializer_derivation(&pointee_type)))
                         })
                         .collect::<Result<Box<[_]>, _>>()?;
 
@@ -201,42 +362,149 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
                     .field_serializer_name_sym
                     .and_then(|sym| msg.symbols.get(sym as usize))
                 {
-                    let Some((super_serializer, _)): Option<(
-                        Arc<dyn EntityClassSerializer>,
-                        bool,
-                    )> = serializers.get(serializer_name).cloned() else {
-                        return Err(std::io::Error::other("Missing serializer for field"));
+                    let Some(class) = serializers.get(serializer_name) else {
+                        return Err(ParserError::UnknownSerializer {
+                            context: context(),
+                            class_name: serializer_name.clone(),
+                        });
                     };
 
-                    super_serializer.serializer_derivation(field_type)
+                    nested_schema = Some(class.schema.clone());
+                    class.serializer.serializer_derivation(field_type)
                 } else {
-                    get_serializer(field_type, var_name, encoder, field_pb)?
+                    get_serializer(field_type, var_name, encoder, field_pb).map_err(unsupported)?
                 };
 
-                fields_cache[field_idx as usize] = Some((var_name, serializer.clone()));
+                let field_schema =
+                    FieldSchema::new(field_pb, &msg.symbols, field_type, nested_schema);
 
-                serializer_fields.push((var_name, serializer))
+                fields_cache[field_idx as usize] =
+                    Some((var_name, serializer.clone(), field_schema.clone()));
+
+                serializer_fields.push((var_name, serializer));
+                schema_fields.push(field_schema);
             }
 
-            let (serializer_creator, serialize_baseline) = if let Some(&serializer_creator) = self
-                .entity_serializer_creators
-                .get(serializer_name.as_str())
-            {
-                (serializer_creator, true)
-            } else {
-                (
-                    UnknownEntitySerializer::new_serializer as EntitySerializerCreator,
-                    false,
-                )
-            };
+            let schema = Arc::new(ClassSchema {
+                name: Arc::from(serializer_name.as_str()),
+                version: serializer_version,
+                fields: schema_fields.into_boxed_slice(),
+            });
+
+            let registered_creator = self.class_creator(&schema);
+
+            let (serializer_creator, serialize_baseline) =
+                if let Some(serializer_creator) = registered_creator {
+                    (serializer_creator, true)
+                } else if let Some(serializer_creator) = self.default_entity_serializer_creator {
+                    (serializer_creator, true)
+                } else {
+                    (
+                        UnknownEntitySerializer::new_serializer as EntitySerializerCreator,
+                        false,
+                    )
+                };
 
             serializers.insert(
                 serializer_name.clone(),
-                (serializer_creator(serializer_fields), serialize_baseline),
+                ClassSerializer {
+                    serializer: serializer_creator(serializer_fields),
+                    serialize_baseline,
+                    registered: registered_creator.is_some(),
+                    schema,
+                },
             );
         }
 
         self.entity_serializers = serializers;
+        self.validate_entity_bindings()
+    }
+
+    /// the registered creator of a class,
+    /// registrations of the user take precedence over the built-in classes of the game state
+    fn class_creator(&self, schema: &ClassSchema) -> Option<EntitySerializerCreator> {
+        let user_creator = self
+            .entity_serializer_creators
+            .get(schema.name.as_ref())
+            .copied()
+            .or_else(|| {
+                self.entity_serializer_matchers
+                    .iter()
+                    .find(|(matcher, _)| matcher.matches(&schema.name))
+                    .map(|(_, creator)| *creator)
+            });
+        let game_state_creator = self
+            .state
+            .game_state()
+            .and_then(|_| game_state_serializer(schema));
+
+        if user_creator.is_some() && game_state_creator.is_some() {
+            warn!(
+                "Entity serializer for '{}' replaces the built-in class of the game state, \
+                 the game state skips these entities",
+                schema.name
+            );
+        }
+
+        user_creator.or(game_state_creator)
+    }
+
+    #[cold]
+    fn validate_entity_bindings(&mut self) -> Result<(), ParserError> {
+        let mut binding_errors = Vec::new();
+
+        for (name, class) in &self.entity_serializers {
+            if class.registered {
+                binding_errors.extend(
+                    class
+                        .serializer
+                        .binding_errors()
+                        .iter()
+                        .map(|err| (name.clone(), err.clone())),
+                );
+            }
+        }
+
+        let game_state_classes = self
+            .state
+            .game_state()
+            .map_or(&[][..], |_| &GAME_STATE_CLASSES[..]);
+        let registered_names = self
+            .entity_serializer_creators
+            .keys()
+            .chain(game_state_classes.iter().map(|(name, _)| name));
+
+        for &name in registered_names {
+            if !self.entity_serializers.contains_key(name) {
+                binding_errors.push((name.to_string(), BindingError::MissingClass));
+            }
+        }
+
+        for (matcher, _) in &self.entity_serializer_matchers {
+            if !self.entity_serializers.keys().any(|n| matcher.matches(n)) {
+                binding_errors.push((matcher.to_string(), BindingError::MissingClass));
+            }
+        }
+        binding_errors.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+        for (class_name, err) in &binding_errors {
+            warn!("Invalid binding in {class_name}: {err}");
+        }
+
+        self.binding_errors = binding_errors;
+
+        if self.strict_entity_bindings && !self.binding_errors.is_empty() {
+            // the first invalid binding, every binding is listed in the errors
+            let (class_name, err) = &self.binding_errors[0];
+            return Err(ParserError::InvalidBindings {
+                context: ErrorContext {
+                    entity_class: Some(Arc::from(class_name.as_str())),
+                    field: err.field_name().map(Arc::from),
+                    ..Default::default()
+                },
+                errors: self.binding_errors.clone(),
+            });
+        }
 
         Ok(())
     }
@@ -244,15 +512,17 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
     pub(super) fn handle_packet_entities(
         &mut self,
         msg: protobuf::CsvcMsgPacketEntities,
-    ) -> Result<(), std::io::Error> {
+    ) -> Result<(), ParserError> {
         let (Some(data), Some(entries)) = (msg.entity_data, msg.updated_entries) else {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
+            return Err(ParserError::invalid_data(
                 "Missing data or number of entries in packet entities",
             ));
         };
 
         let has_pvs_vis_bits = msg.has_pvs_vis_bits_deprecated.unwrap_or(0) > 0;
+        let notify_created = self.event_manager.is_subscribed::<EntityCreatedEvent>();
+        let notify_updated = self.event_manager.is_subscribed::<EntityUpdatedEvent>();
+        let notify_deleted = self.event_manager.is_subscribed::<EntityDeletedEvent>();
 
         // let len = (data.len() << 3) as u64;
         let mut r = BitReader::endian(Cursor::new(data.as_ref()), bitstream_io::LittleEndian);
@@ -263,71 +533,117 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
             let cmd = r.read_unsigned::<2, u8>()?;
 
             if cmd & 1 == 0 {
-                if cmd & 2 != 0 {
+                let created = cmd & 2 != 0;
+                if created {
                     // create entity
                     let class_id: u32 = r.read_var(self.class_id_size)?;
                     let serial = r.read_unsigned::<17, u32>()?;
                     let _unk_0 = r.read_varint_u64()?;
 
-                    let Some(class_name) = self.class_info.get(&class_id).map(|s| s.as_str())
-                    else {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            format!("Unknown class id: {class_id}"),
-                        ));
+                    let Some(class_name) = self.class_info.get(&class_id).cloned() else {
+                        return Err(ParserError::UnknownClassId {
+                            context: ErrorContext::default(),
+                            class_id,
+                        });
                     };
 
-                    let Some((serializer, serialize_baseline)) =
-                        self.entity_serializers.get(class_name)
-                    else {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            format!("Unknown serializer: {class_name}"),
-                        ));
+                    let Some(class) = self.entity_serializers.get(class_name.as_ref()) else {
+                        return Err(ParserError::UnknownSerializer {
+                            context: ErrorContext {
+                                entity_class: Some(class_name.clone()),
+                                ..Default::default()
+                            },
+                            class_name: class_name.to_string(),
+                        });
                     };
 
-                    let serializer = serializer.clone();
+                    let serializer = class.serializer.clone();
+                    let schema = class.schema.clone();
 
                     let entity = EntityItem {
                         index: idx as u32,
                         serial,
-                        item: if *serialize_baseline {
-                            self.parse_entity_from_baseline(class_id, serializer.as_ref())?
+                        item: if class.serialize_baseline {
+                            self.parse_entity_from_baseline(class_id, &schema, serializer.as_ref())?
                         } else {
                             serializer.new_entity()
                         },
+                        class_name,
                         serializer,
+                        schema,
                     };
 
-                    self.state.entities.insert(idx as usize, entity);
+                    // the previous entity in the slot is replaced without a delete command
+                    if let Some(replaced) = self.state.entities.insert(idx as usize, entity)
+                        && notify_deleted
+                    {
+                        self.notify_entity_deleted(replaced)?;
+                    }
                 } else if has_pvs_vis_bits && r.read_unsigned::<2, u8>()? & 1 != 0 {
                     continue;
                 }
 
                 let Some(entity) = self.state.entities.get_mut(idx as usize) else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Entity at index {idx} not found for update"),
-                    ));
+                    return Err(ParserError::invalid_data(format!(
+                        "Entity at index {idx} not found for update"
+                    )));
                 };
 
-                if entry == entries - 1 && entity.item.is::<UnknownEntity>() {
-                    // if the last entity is an unknown entity, we can skip reading the fields
-                    continue;
-                }
+                let serial = entity.serial;
+                let handle = entity.get_handle();
+                let class_name = entity.class_name.clone();
+
+                let previous = if !created && notify_updated && self.track_previous_entity_values {
+                    Some(entity.serializer.clone_entity(entity.item.as_ref())?)
+                } else {
+                    None
+                };
+                let mut changed_fields = Vec::new();
+
+                // if the last entity is an unknown entity, we can skip reading the fields
+                if entry != entries - 1 || !entity.item.is::<UnknownEntity>() {
+                    read_field_paths(&mut r, &mut self.field_path_cache)?;
 
-                read_field_paths(&mut r, &mut self.field_path_cache)?;
+                    for field_path in &self.field_path_cache {
+                        let path = field_path.to_slice();
+                        entity
+                            .serializer
+                            .decode(Some(entity.item.as_mut()), path, &mut r)
+                            .map_err(|err| field_decode_error(&entity.schema, path, err))?;
+                    }
 
-                for field_path in &self.field_path_cache {
-                    entity.serializer.decode(
-                        Some(entity.item.as_mut()),
-                        field_path.to_slice(),
-                        &mut r,
-                    )?;
+                    if !created && notify_updated {
+                        changed_fields.extend_from_slice(&self.field_path_cache);
+                    }
+
+                    self.field_path_cache.clear();
                 }
 
-                self.field_path_cache.clear();
-            } else if self.state.entities.delete(idx as usize).is_none() {
+                if created && notify_created {
+                    self.notify_listeners(EntityCreatedEvent {
+                        index: idx as u32,
+                        serial,
+                        handle,
+                        class_name,
+                    })?;
+                } else if !created && notify_updated {
+                    let schema = entity.schema.clone();
+
+                    self.notify_listeners(EntityUpdatedEvent {
+                        index: idx as u32,
+                        serial,
+                        handle,
+                        class_name,
+                        changed_fields,
+                        schema,
+                        previous,
+                    })?;
+                }
+            } else if let Some(entity) = self.state.entities.delete(idx as usize) {
+                if notify_deleted {
+                    self.notify_entity_deleted(entity)?;
+                }
+            } else {
                 error!("Entity at index {idx} not found for deletion");
             }
         }
@@ -337,14 +653,25 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
         //     error!("packet entities did not consume all data: {bits}");
         // }
 
-        Ok(())
+        self.update_game_state()
+    }
+
+    pub(super) fn notify_entity_deleted(&mut self, entity: EntityItem) -> Result<(), ParserError> {
+        self.notify_listeners(EntityDeletedEvent {
+            index: entity.index,
+            serial: entity.serial,
+            handle: entity.get_handle(),
+            class_name: entity.class_name,
+            item: entity.item,
+        })
     }
 
     fn parse_entity_from_baseline(
         &mut self,
         class_id: u32,
+        schema: &ClassSchema,
         serializer: &dyn EntityClassSerializer,
-    ) -> Result<Box<dyn Any + Send + Sync>, std::io::Error> {
+    ) -> Result<Box<dyn Any + Send + Sync>, ParserError> {
         // try to get entity from cache
         let Some(instance_baseline) = self.instance_baseline.as_mut() else {
             return Ok(serializer.new_entity());
@@ -353,7 +680,7 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
         let baseline_key = class_id.to_string();
 
         if let Some(entity) = instance_baseline.get_cached(&baseline_key) {
-            return serializer.clone_entity(entity.as_ref());
+            return Ok(serializer.clone_entity(entity.as_ref())?);
         }
 
         // if not found, create a new entity
@@ -368,11 +695,10 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
             read_field_paths(&mut baseline_reader, &mut self.field_path_cache)?;
 
             for field_path in &self.field_path_cache {
-                serializer.decode(
-                    Some(entity.as_mut()),
-                    field_path.to_slice(),
-                    &mut baseline_reader,
-                )?;
+                let path = field_path.to_slice();
+                serializer
+                    .decode(Some(entity.as_mut()), path, &mut baseline_reader)
+                    .map_err(|err| field_decode_error(schema, path, err))?;
             }
 
             self.field_path_cache.clear();
@@ -393,3 +719,131 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
         Ok(entity)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity::EntityClass,
+        game_state::classes::{CCSPlayerPawn, CCSWeaponBase},
+    };
+
+    #[derive(EntityClass, Clone, Default, Debug)]
+    struct Weapon {
+        #[entity(name = "m_iClip1")]
+        clip: i32,
+    }
+
+    fn parser() -> CsDemoParser<Cursor<Vec<u8>>> {
+        let mut header = b"PBDEMS2\0".to_vec();
+        header.resize(16, 0);
+
+        CsDemoParser::new(Cursor::new(header)).unwrap()
+    }
+
+    fn field(name: &str, nested: Option<ClassSchema>) -> FieldSchema {
+        FieldSchema {
+            name: Arc::from(name),
+            var_type: Arc::from("int32"),
+            encoder: None,
+            send_node: None,
+            bit_count: None,
+            low_value: None,
+            high_value: None,
+            encode_flags: None,
+            serializer_name: nested.as_ref().map(|n| n.name.clone()),
+            polymorphic_types: Box::new([]),
+            indexed: false,
+            nested: nested.map(Arc::new),
+        }
+    }
+
+    fn class(name: &str, fields: Vec<FieldSchema>) -> ClassSchema {
+        ClassSchema {
+            name: Arc::from(name),
+            version: None,
+            fields: fields.into_boxed_slice(),
+        }
+    }
+
+    fn weapon_class(name: &str) -> ClassSchema {
+        let item = class("CEconItemView", vec![field("m_iItemDefinitionIndex", None)]);
+        let attributes = class("CAttributeContainer", vec![field("m_Item", Some(item))]);
+
+        class(
+            name,
+            vec![
+                field("m_AttributeManager", Some(attributes)),
+                field("m_iClip1", None),
+            ],
+        )
+    }
+
+    /// checks which struct the registered creator of the class decodes into
+    fn decodes_as<T: 'static>(
+        parser: &CsDemoParser<Cursor<Vec<u8>>>,
+        schema: &ClassSchema,
+    ) -> bool {
+        parser
+            .class_creator(schema)
+            .is_some_and(|creator| creator(Vec::new()).new_entity().is::<T>())
+    }
+
+    #[test]
+    fn matcher_of_the_user_takes_precedence_over_the_game_state() {
+        let mut parser = parser();
+        parser.register_entity_serializer_matching(
+            ClassMatcher::prefix("CWeapon"),
+            Weapon::new_serializer,
+        );
+        parser.enable_game_state().unwrap();
+
+        assert!(decodes_as::<Weapon>(&parser, &weapon_class("CWeaponAWP")));
+        assert!(decodes_as::<CCSWeaponBase>(&parser, &weapon_class("CAK47")));
+    }
+
+    #[test]
+    fn registration_of_the_user_takes_precedence_over_the_game_state() {
+        let mut parser = parser();
+        parser.enable_game_state().unwrap();
+        parser.register_entity_serializer("CCSPlayerPawn", Weapon::new_serializer);
+
+        assert!(decodes_as::<Weapon>(
+            &parser,
+            &class("CCSPlayerPawn", Vec::new())
+        ));
+        assert!(!decodes_as::<CCSPlayerPawn>(
+            &parser,
+            &class("CCSPlayerPawn", Vec::new())
+        ));
+    }
+
+    #[test]
+    fn game_state_classes_are_decoded_only_while_enabled() {
+        let mut parser = parser();
+        assert!(parser.class_creator(&weapon_class("CAK47")).is_none());
+
+        parser.enable_game_state().unwrap();
+        assert!(decodes_as::<CCSWeaponBase>(&parser, &weapon_class("CAK47")));
+        assert!(decodes_as::<CCSPlayerPawn>(
+            &parser,
+            &class("CCSPlayerPawn", Vec::new())
+        ));
+    }
+
+    #[test]
+    fn field_decode_error_names_the_class_and_the_field() {
+        let schema = class("CCSPlayerPawn", vec![field("m_iHealth", None)]);
+        let source = std::io::Error::from(std::io::ErrorKind::InvalidData);
+
+        let err = field_decode_error(&schema, &[0], source).with_frame(5, 7);
+        let context = err.context().unwrap();
+
+        assert_eq!(context.entity_class.as_deref(), Some("CCSPlayerPawn"));
+        assert_eq!(context.field.as_deref(), Some("m_iHealth"));
+        assert!(
+            err.to_string()
+                .contains("at tick 5, command 7 in class CCSPlayerPawn, field m_iHealth")
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use std::fmt::Write;
+
+use foldhash::{HashMap, HashMapExt, HashSet, HashSetExt};
+use log::warn;
+
+use crate::entity::{
+    decoder::{BASIC_ENCODINGS, FIELD_ENCODER_OVERRIDES},
+    field::FieldType,
+    schema::{ClassSchema, FieldSchema},
+};
+
+/// identifiers which have to be escaped with `r#`
+pub const RUST_KEYWORDS: [&str; 51] = [
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "async", "await", "dyn", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield", "try",
+];
+
+static MANUAL_FIELD_OVERRIDES: std::sync::LazyLock<HashMap<&'static str, &'static str>> =
+    std::sync::LazyLock::new(|| {
+        let mut map = HashMap::new();
+
+        map.insert("m_nCollisionGroup", "n_collision_group");
+        map.insert("m_CollisionGroup", "collision_group");
+        map.insert("m_iRecoilIndex", "recoil_index_int");
+        map.insert("m_flRecoilIndex", "recoil_index_float");
+
+        map
+    });
+
+static HUNGARIAN_NOTATION_REGEX: std::sync::LazyLock<regex::Regex> =
+    std::sync::LazyLock::new(|| regex::Regex::new(r"^[a-z]{0,2}[A-Z]").unwrap());
+
+/// generates `#[derive(EntityClass)]` structs for the given schemas,
+/// see `CsDemoParser::class_schemas`
+///
+/// every field is included, fields with types the parser can't decode
+/// are left as comments. take only the classes and fields you need,
+/// every registered field is decoded on every update
+pub fn generate_entity_classes<'a>(classes: impl IntoIterator<Item = &'a ClassSchema>) -> String {
+    let mut out = String::with_capacity(1 << 20);
+
+    for class in classes {
+        let _ = writeln!(out, "#[derive(EntityClass, Clone, Default)]");
+        let _ = writeln!(out, "pub struct {} {{", class.name);
+
+        let mut deduplicate_escaped_name = HashSet::with_capacity(class.fields.len());
+
+        for field in &class.fields {
+            let type_name = match rust_type(field) {
+                Ok(type_name) => type_name,
+                Err(err) => {
+                    warn!("Skipping field {} of {}: {err}", field.name, class.name);
+                    let _ = writeln!(out, "    // {}: {} ({err})", field.name, field.var_type);
+                    continue;
+                }
+            };
+
+            if !field.polymorphic_types.is_empty() {
+                let _ = writeln!(
+                    out,
+                    "    /// polymorphic field: {}",
+                    field.polymorphic_types.join(", ")
+                );
+            }
+
+            let _ = writeln!(out, "    #[entity(name = \"{}\")]", field.name);
+            let _ = writeln!(
+                out,
+                "    pub {}: {},",
+                field_ident(&field.name, &mut deduplicate_escaped_name),
+                type_name
+            );
+        }
+
+        let _ = writeln!(out, "}}\n");
+    }
+
+    out
+}
+
+/// maps a field to the type the serializer of `get_serializer` decodes into
+fn rust_type(field: &FieldSchema) -> Result<String, String> {
+    if !field.polymorphic_types.is_empty() {
+        return Ok("PolymorphicEntity".to_string());
+    }
+
+    let field_type = FieldType::new(&field.var_type).map_err(|err| err.to_string())?;
+
+    let type_str = if let Some(serializer_name) = &field.serializer_name {
+        serializer_name.to_string()
+    } else {
+        primitive_type(&field_type, &field.name, field.encoder.as_deref())?.to_string()
+    };
+
+    Ok(if field_type.is_optional {
+        format!("Option<{type_str}>")
+    } else if field.indexed {
+        format!("Vec<{type_str}>")
+    } else {
+        type_str
+    })
+}
+
+fn primitive_type(
+    field_type: &FieldType,
+    var_name: &str,
+    encoder: Option<&str>,
+) -> Result<&'static str, String> {
+    let var_type = field_type.get_var_type();
+    let Some(&(net_type, components)) = BASIC_ENCODINGS.get(var_type) else {
+        return Err(format!("No serializer found for type: {var_type}"));
+    };
+
+    let field_override = FIELD_ENCODER_OVERRIDES.get(var_name).copied();
+
+    Ok(match (net_type, components) {
+        ("NET_DATA_TYPE_UINT64", 1) => match field_override {
+            Some("NET_DATA_TYPE_FLOAT32") => "f32",
+            None => "u64",
+            Some(ty) => return Err(format!("Unsupported field type warp for UINT64: {ty}")),
+        },
+        ("NET_DATA_TYPE_INT64", 1) => "i64",
+        // other encoders decode QAngle as a plain vector
+        ("NET_DATA_TYPE_FLOAT32", 3)
+            if var_type == "QAngle" && matches!(encoder, Some("qangle" | "qangle_precise")) =>
+        {
+            "QAngle"
+        }
+        ("NET_DATA_TYPE_FLOAT32", 1) => "f32",
+        ("NET_DATA_TYPE_FLOAT32", 2) => "Vector2",
+        ("NET_DATA_TYPE_FLOAT32", 3) => "Vector3",
+        ("NET_DATA_TYPE_FLOAT32", 4) => "Vector4",
+        ("NET_DATA_TYPE_FLOAT32", 6) => "Transform6",
+        ("NET_DATA_TYPE_STRING", 1) => "String",
+        ("NET_DATA_TYPE_BOOL", 1) => "bool",
+        _ => {
+            return Err(format!(
+                "Unsupported net type: {net_type} ({var_type}) with {components} components"
+            ));
+        }
+    })
+}
+
+/// converts a field name to a unique snake_case identifier
+fn field_ident(var_name: &str, deduplicate_escaped_name: &mut HashSet<String>) -> String {
+    // the variable naming scheme of value is quite inconsistent
+    // sometimes hungarian notation is used
+    // sometimes only m_ prefixes are used
+    // sometimes hungarian notations are used without m_ prefixes
+    // also for camel casing
+    // sometimes `ID` and sometimes `Id`
+    // we have to take all of this into account when converting to snake_case
+
+    let escaped_var_name = if let Some(override_var_name) = MANUAL_FIELD_OVERRIDES.get(var_name) {
+        override_var_name.to_string()
+    } else {
+        let mut offset = if var_name.starts_with("m_") { 2 } else { 0 };
+
+        if let Some(m) = HUNGARIAN_NOTATION_REGEX.find(&var_name[offset..]) {
+            offset += m.len() - 1;
+        }
+
+        let escaped_var_name = to_snake_case(&var_name[offset..]);
+        let mut seq = 0;
+        loop {
+            let sequenced = if seq == 0 {
+                escaped_var_name.clone()
+            } else {
+                format!("{escaped_var_name}_dup{seq}")
+            };
+
+            if deduplicate_escaped_name.insert(sequenced.clone()) {
+                break sequenced;
+            }
+
+            seq += 1;
+        }
+    };
+
+    if RUST_KEYWORDS.contains(&escaped_var_name.as_str()) {
+        format!("r#{escaped_var_name}")
+    } else {
+        escaped_var_name
+    }
+}
+
+fn to_snake_case(camel: &str) -> String {
+    let mut result = String::with_capacity(camel.len() + 8);
+    let mut prev_was_upper = true;
+
+    for c in camel.chars() {
+        if c == '_' {
+            result.push('_');
+            prev_was_upper = true;
+        } else if c.is_uppercase() {
+            if !prev_was_upper {
+                result.push('_');
+            }
+            result.push(c.to_ascii_lowercase());
+            prev_was_upper = true;
+        } else {
+            result.push(c);
+            prev_was_upper = false;
+        }
+    }
+
+    result
+}
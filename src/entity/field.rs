@@ -39,7 +39,7 @@ const LIST_GENERIC_TYPE: [&str; 3] = [
     "CUtlVector",
 ];
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FieldType {
     pub base_type: String,
     pub generic_type: Option<Box<FieldType>>,
@@ -63,6 +63,7 @@ impl FieldPath {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct FieldPathFixed([u32; 8]);
 
 impl FieldPathFixed {
@@ -1,6 +1,12 @@
-use std::{any::Any, sync::Arc};
+use std::{any::Any, marker::PhantomData, sync::Arc};
 
-use crate::entity::serializer::{EntityClassSerializer, EntityField};
+use crate::entity::{
+    schema::ClassSchema,
+    serializer::{
+        EntityClassSerializer, EntityField, FromEntityInt,
+        dynamic::{DynamicEntity, Value},
+    },
+};
 
 const MAX_ENTITIES_IN_LIST: usize = 512;
 const MAX_ENTITY_LISTS: usize = 64;
@@ -10,18 +16,132 @@ const ENTITY_OFFSET_MASK: usize = MAX_ENTITIES_IN_LIST - 1;
 
 const MAX_EDICT_BITS: usize = 14;
 const ENTITY_HANDLE_INDEX_MASK: u64 = (1 << MAX_EDICT_BITS) - 1;
+const NUM_SERIAL_NUMBER_BITS: usize = 17;
+
+/// a handle field (`CHandle`, `EHandle`, `CEntityHandle`) pointing to an entity of type T
+///
+/// usable in `EntityClass` structs as is or in a `Vec`
+pub struct EntityHandle<T> {
+    raw: u64,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> EntityHandle<T> {
+    /// the handle of an empty field
+    pub const INVALID: Self = Self::from_raw((1 << (MAX_EDICT_BITS + NUM_SERIAL_NUMBER_BITS)) - 1);
+
+    pub const fn from_raw(raw: u64) -> Self {
+        Self {
+            raw,
+            _marker: PhantomData,
+        }
+    }
+
+    pub const fn raw(&self) -> u64 {
+        self.raw
+    }
+
+    pub const fn index(&self) -> u32 {
+        (self.raw & ENTITY_HANDLE_INDEX_MASK) as u32
+    }
+
+    pub const fn serial(&self) -> u32 {
+        (self.raw >> MAX_EDICT_BITS) as u32
+    }
+
+    /// false for empty fields, an index of all ones is never a valid entity
+    pub const fn is_valid(&self) -> bool {
+        self.raw & ENTITY_HANDLE_INDEX_MASK != ENTITY_HANDLE_INDEX_MASK
+    }
+
+    /// returns the entity if it still exists and is decoded as T
+    pub fn resolve<'a>(&self, entities: &'a EntityList) -> Option<&'a T>
+    where
+        T: EntityField,
+    {
+        entities.get_entity_by_handle(self.raw)
+    }
+
+    /// returns the entity if it still exists regardless of its type
+    pub fn resolve_item<'a>(&self, entities: &'a EntityList) -> Option<&'a EntityItem> {
+        entities.get_by_handle(self.raw)
+    }
+}
+
+impl<T> Clone for EntityHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for EntityHandle<T> {}
+
+impl<T> Default for EntityHandle<T> {
+    fn default() -> Self {
+        Self::INVALID
+    }
+}
+
+impl<T> PartialEq for EntityHandle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl<T> Eq for EntityHandle<T> {}
+
+impl<T> std::hash::Hash for EntityHandle<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.raw.hash(state);
+    }
+}
+
+impl<T> std::fmt::Debug for EntityHandle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_valid() {
+            write!(f, "EntityHandle({}:{})", self.index(), self.serial())
+        } else {
+            write!(f, "EntityHandle(invalid)")
+        }
+    }
+}
+
+impl<T> From<u64> for EntityHandle<T> {
+    fn from(raw: u64) -> Self {
+        Self::from_raw(raw)
+    }
+}
+
+impl<T: 'static> FromEntityInt for EntityHandle<T> {
+    fn from_entity_int(value: i128) -> Self {
+        Self::from_raw(value as u64)
+    }
+}
 
 pub struct EntityItem {
     pub index: u32,
     pub serial: u32,
+    /// network name of the entity class, e.g. CCSPlayerPawn
+    pub class_name: Arc<str>,
     pub item: Box<dyn Any + Send + Sync>,
     pub serializer: Arc<dyn EntityClassSerializer>,
+    pub schema: Arc<ClassSchema>,
 }
 
 impl EntityItem {
     pub fn get_handle(&self) -> u64 {
         ((self.serial as u64) << MAX_EDICT_BITS) | (self.index as u64)
     }
+
+    /// returns the entity if its class is decoded dynamically
+    pub fn as_dynamic(&self) -> Option<&DynamicEntity> {
+        self.item.downcast_ref::<DynamicEntity>()
+    }
+
+    /// returns a property of a dynamically decoded entity by name
+    pub fn get(&self, name: &str) -> Option<Value> {
+        self.as_dynamic()?.get(name)
+    }
 }
 
 /// a simple implementation of CConcreteEntityList
@@ -138,6 +258,11 @@ impl EntityList {
     }
 
     pub fn get_entity_by_handle<T: EntityField>(&self, handle: u64) -> Option<&T> {
+        self.get_by_handle(handle)?.item.downcast_ref::<T>()
+    }
+
+    /// returns the entity unless its slot has been reused since the handle was taken
+    pub fn get_by_handle(&self, handle: u64) -> Option<&EntityItem> {
         let idx = (handle & ENTITY_HANDLE_INDEX_MASK) as usize;
         let entity = self.get(idx)?;
         let serial = (handle >> MAX_EDICT_BITS) as u32;
@@ -146,7 +271,7 @@ impl EntityList {
             return None;
         }
 
-        entity.item.downcast_ref::<T>()
+        Some(entity)
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &EntityItem> {
@@ -168,3 +293,31 @@ impl EntityList {
             .filter_map(|item| item.item.downcast_ref::<T>().map(|e| (item, e)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_handle_is_not_valid() {
+        assert!(!EntityHandle::<()>::INVALID.is_valid());
+        assert!(!EntityHandle::<()>::default().is_valid());
+    }
+
+    #[test]
+    fn handle_with_an_index_is_valid() {
+        let handle = EntityHandle::<()>::from_raw(5 | 3 << MAX_EDICT_BITS);
+
+        assert!(handle.is_valid());
+        assert_eq!(handle.index(), 5);
+        assert_eq!(handle.serial(), 3);
+        assert!(EntityHandle::<()>::from_raw(0).is_valid());
+    }
+
+    #[test]
+    fn any_serial_with_the_invalid_index_is_not_valid() {
+        let handle = EntityHandle::<()>::from_raw(ENTITY_HANDLE_INDEX_MASK | 7 << MAX_EDICT_BITS);
+
+        assert!(!handle.is_valid());
+    }
+}
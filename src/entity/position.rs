@@ -0,0 +1,105 @@
+use crate::entity::{
+    EntityClass,
+    serializer::{dynamic::DynamicEntity, vector::Vector3},
+};
+
+const CELL_BITS: u32 = 9;
+const CELL_WIDTH: f32 = (1 << CELL_BITS) as f32;
+const MAX_COORD: f32 = 16384.0;
+
+/// converts a cell coordinate and the offset inside the cell to a world coordinate
+#[inline(always)]
+pub fn cell_to_world(cell: u32, offset: f32) -> f32 {
+    cell as f32 * CELL_WIDTH - MAX_COORD + offset
+}
+
+/// origin of an entity as networked by `CBodyComponent`,
+/// embed it with `#[entity(flatten)]` into classes such as `CCSPlayerPawn`
+///
+/// the cell and the offset are sent separately,
+/// `world` combines the latest values of both
+#[derive(EntityClass, Clone, Default, Debug, PartialEq)]
+pub struct Position {
+    #[entity(name = "CBodyComponent.m_cellX")]
+    pub cell_x: u32,
+    #[entity(name = "CBodyComponent.m_cellY")]
+    pub cell_y: u32,
+    #[entity(name = "CBodyComponent.m_cellZ")]
+    pub cell_z: u32,
+    #[entity(name = "CBodyComponent.m_vecX")]
+    pub offset_x: f32,
+    #[entity(name = "CBodyComponent.m_vecY")]
+    pub offset_y: f32,
+    #[entity(name = "CBodyComponent.m_vecZ")]
+    pub offset_z: f32,
+}
+
+impl Position {
+    /// absolute world position
+    pub fn world(&self) -> Vector3 {
+        Vector3 {
+            x: cell_to_world(self.cell_x, self.offset_x),
+            y: cell_to_world(self.cell_y, self.offset_y),
+            z: cell_to_world(self.cell_z, self.offset_z),
+        }
+    }
+
+    /// reads the position of a dynamically decoded entity,
+    /// None if it has no `CBodyComponent`
+    pub fn from_dynamic(entity: &DynamicEntity) -> Option<Self> {
+        let cell = |name: &str| entity.get(name)?.as_u64().map(|v| v as u32);
+        let offset = |name: &str| entity.get(name)?.as_f32();
+
+        Some(Self {
+            cell_x: cell("CBodyComponent.m_cellX")?,
+            cell_y: cell("CBodyComponent.m_cellY")?,
+            cell_z: cell("CBodyComponent.m_cellZ")?,
+            offset_x: offset("CBodyComponent.m_vecX")?,
+            offset_y: offset("CBodyComponent.m_vecY")?,
+            offset_z: offset("CBodyComponent.m_vecZ")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn world_combines_cell_and_offset() {
+        let position = Position {
+            cell_x: 32,
+            cell_y: 33,
+            cell_z: 31,
+            offset_x: 0.0,
+            offset_y: 12.5,
+            offset_z: 511.0,
+        };
+
+        assert_eq!(
+            position.world(),
+            Vector3 {
+                x: 0.0,
+                y: 524.5,
+                z: -1.0,
+            }
+        );
+    }
+
+    #[test]
+    fn world_of_the_first_cell_is_the_lowest_coordinate() {
+        let position = Position {
+            offset_x: 1.0,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            position.world(),
+            Vector3 {
+                x: 1.0 - MAX_COORD,
+                y: -MAX_COORD,
+                z: -MAX_COORD,
+            }
+        );
+    }
+}
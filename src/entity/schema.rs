@@ -0,0 +1,356 @@
+use std::{fmt::Write, sync::Arc};
+
+use crate::{entity::field::FieldType, protobuf};
+
+/// layout of a serializer as described by the send tables
+#[derive(Debug)]
+pub struct ClassSchema {
+    pub name: Arc<str>,
+    pub version: Option<i32>,
+    /// top-level fields, indexed by the first component of a field path
+    pub fields: Box<[FieldSchema]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldSchema {
+    pub name: Arc<str>,
+    pub var_type: Arc<str>,
+    pub encoder: Option<Arc<str>>,
+    pub send_node: Option<Arc<str>>,
+    pub bit_count: Option<i32>,
+    pub low_value: Option<f32>,
+    pub high_value: Option<f32>,
+    pub encode_flags: Option<i32>,
+    /// name of the serializer the field points to, if any
+    pub serializer_name: Option<Arc<str>>,
+    /// serializer names a polymorphic field can take
+    pub polymorphic_types: Box<[Arc<str>]>,
+    /// vectors and arrays take an element index as the next path component
+    pub indexed: bool,
+    /// schema of the serializer the field points to, if any
+    pub nested: Option<Arc<ClassSchema>>,
+}
+
+impl FieldSchema {
+    pub(crate) fn new(
+        field_pb: &protobuf::ProtoFlattenedSerializerFieldT,
+        symbols: &[String],
+        field_type: &FieldType,
+        nested: Option<Arc<ClassSchema>>,
+    ) -> Self {
+        let symbol = |sym: Option<i32>| -> Option<Arc<str>> {
+            sym.and_then(|sym| symbols.get(sym as usize))
+                .map(|s| Arc::from(s.as_str()))
+        };
+
+        // mirrors the wrapping order of `serializer_derivation`
+        let indexed = !field_type.is_optional
+            && ((field_type.array_size > 0 && field_type.base_type != "char")
+                || field_type.base_type == "CUtlVector"
+                || field_type.base_type == "CNetworkUtlVectorBase"
+                || field_type.base_type == "CUtlVectorEmbeddedNetworkVar");
+
+        Self {
+            name: symbol(field_pb.var_name_sym).unwrap_or_default(),
+            var_type: symbol(field_pb.var_type_sym).unwrap_or_default(),
+            encoder: symbol(field_pb.var_encoder_sym),
+            send_node: symbol(field_pb.send_node_sym).filter(|s| !s.is_empty()),
+            bit_count: field_pb.bit_count,
+            low_value: field_pb.low_value,
+            high_value: field_pb.high_value,
+            encode_flags: field_pb.encode_flags,
+            serializer_name: symbol(field_pb.field_serializer_name_sym),
+            polymorphic_types: field_pb
+                .polymorphic_types
+                .iter()
+                .filter_map(|pb| symbol(pb.polymorphic_field_serializer_name_sym))
+                .collect(),
+            indexed,
+            nested,
+        }
+    }
+}
+
+impl ClassSchema {
+    pub fn field_index(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|f| f.name.as_ref() == name)
+    }
+
+    /// checks if a dotted name, e.g. `m_AttributeManager.m_Item.m_iItemDefinitionIndex`,
+    /// resolves to a field of this class or of its nested classes
+    pub fn has_field(&self, name: &str) -> bool {
+        match name.split_once('.') {
+            Some((field, rest)) => self
+                .field_index(field)
+                .and_then(|idx| self.fields[idx].nested.as_deref())
+                .is_some_and(|nested| nested.has_field(rest)),
+            None => self.field_index(name).is_some(),
+        }
+    }
+
+    /// converts a field path to a dotted name, e.g. `CBodyComponent.m_cellX`,
+    /// element indices of vectors and arrays are included as a component
+    ///
+    /// the name is truncated at the first component the schema can't resolve
+    pub fn resolve_path(&self, path: &[u32]) -> Option<String> {
+        let mut name = String::new();
+        self.write_path(path, &mut name)?;
+
+        Some(name)
+    }
+
+    fn write_path(&self, path: &[u32], out: &mut String) -> Option<()> {
+        let (&idx, mut rest) = path.split_first()?;
+        let field = self.fields.get(idx as usize)?;
+
+        if !out.is_empty() {
+            out.push('.');
+        }
+        out.push_str(&field.name);
+
+        if field.indexed
+            && let Some((&element, next)) = rest.split_first()
+        {
+            let _ = write!(out, ".{element}");
+            rest = next;
+        }
+
+        if !rest.is_empty()
+            && let Some(nested) = &field.nested
+        {
+            nested.write_path(rest, out);
+        }
+
+        Some(())
+    }
+}
+
+/// serializes the schemas into a JSON array,
+/// nested serializers are referenced by name as every serializer is listed on its own
+pub fn to_json<'a>(classes: impl IntoIterator<Item = &'a ClassSchema>) -> String {
+    let mut out = String::with_capacity(1 << 20);
+
+    out.push('[');
+    for (i, class) in classes.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+
+        out.push_str("\n  {\"name\": ");
+        write_json_str(&mut out, &class.name);
+        out.push_str(", \"version\": ");
+        write_json_opt(&mut out, class.version);
+        out.push_str(", \"fields\": [");
+
+        for (j, field) in class.fields.iter().enumerate() {
+            if j > 0 {
+                out.push(',');
+            }
+
+            out.push_str("\n    {\"name\": ");
+            write_json_str(&mut out, &field.name);
+            out.push_str(", \"var_type\": ");
+            write_json_str(&mut out, &field.var_type);
+            out.push_str(", \"encoder\": ");
+            write_json_opt_str(&mut out, field.encoder.as_deref());
+            out.push_str(", \"send_node\": ");
+            write_json_opt_str(&mut out, field.send_node.as_deref());
+            out.push_str(", \"bit_count\": ");
+            write_json_opt(&mut out, field.bit_count);
+            out.push_str(", \"low_value\": ");
+            write_json_opt(&mut out, field.low_value.filter(|v| v.is_finite()));
+            out.push_str(", \"high_value\": ");
+            write_json_opt(&mut out, field.high_value.filter(|v| v.is_finite()));
+            out.push_str(", \"encode_flags\": ");
+            write_json_opt(&mut out, field.encode_flags);
+            out.push_str(", \"serializer\": ");
+            write_json_opt_str(&mut out, field.serializer_name.as_deref());
+            out.push_str(", \"polymorphic_types\": [");
+            for (k, ty) in field.polymorphic_types.iter().enumerate() {
+                if k > 0 {
+                    out.push_str(", ");
+                }
+                write_json_str(&mut out, ty);
+            }
+            out.push_str("]}");
+        }
+
+        if !class.fields.is_empty() {
+            out.push_str("\n  ");
+        }
+        out.push_str("]}");
+    }
+    out.push_str("\n]\n");
+
+    out
+}
+
+fn write_json_opt<T: std::fmt::Display>(out: &mut String, value: Option<T>) {
+    match value {
+        Some(v) => {
+            let _ = write!(out, "{v}");
+        }
+        None => out.push_str("null"),
+    }
+}
+
+fn write_json_opt_str(out: &mut String, value: Option<&str>) {
+    match value {
+        Some(v) => write_json_str(out, v),
+        None => out.push_str("null"),
+    }
+}
+
+fn write_json_str(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str, indexed: bool, nested: Option<ClassSchema>) -> FieldSchema {
+        FieldSchema {
+            name: Arc::from(name),
+            var_type: Arc::from("int32"),
+            encoder: None,
+            send_node: None,
+            bit_count: None,
+            low_value: None,
+            high_value: None,
+            encode_flags: None,
+            serializer_name: nested.as_ref().map(|n| n.name.clone()),
+            polymorphic_types: Box::new([]),
+            indexed,
+            nested: nested.map(Arc::new),
+        }
+    }
+
+    fn class(name: &str, fields: Vec<FieldSchema>) -> ClassSchema {
+        ClassSchema {
+            name: Arc::from(name),
+            version: None,
+            fields: fields.into_boxed_slice(),
+        }
+    }
+
+    fn pawn() -> ClassSchema {
+        let body = class(
+            "CBodyComponent",
+            vec![field("m_cellX", false, None), field("m_vecX", false, None)],
+        );
+        let weapon_services = class(
+            "CPlayer_WeaponServices",
+            vec![field("m_hMyWeapons", true, None)],
+        );
+        let sub_tick = class("CSubTick", vec![field("m_flWhen", false, None)]);
+
+        class(
+            "CCSPlayerPawn",
+            vec![
+                field("m_iHealth", false, None),
+                field("CBodyComponent", false, Some(body)),
+                field("m_pWeaponServices", false, Some(weapon_services)),
+                field("m_subTicks", true, Some(sub_tick)),
+            ],
+        )
+    }
+
+    #[test]
+    fn resolve_path_of_top_level_and_nested_fields() {
+        let pawn = pawn();
+
+        assert_eq!(pawn.resolve_path(&[0]).as_deref(), Some("m_iHealth"));
+        assert_eq!(
+            pawn.resolve_path(&[1, 1]).as_deref(),
+            Some("CBodyComponent.m_vecX")
+        );
+    }
+
+    #[test]
+    fn resolve_path_includes_element_indices() {
+        let pawn = pawn();
+
+        assert_eq!(
+            pawn.resolve_path(&[2, 0, 3]).as_deref(),
+            Some("m_pWeaponServices.m_hMyWeapons.3")
+        );
+        assert_eq!(
+            pawn.resolve_path(&[3, 2, 0]).as_deref(),
+            Some("m_subTicks.2.m_flWhen")
+        );
+        // the length of a vector
+        assert_eq!(pawn.resolve_path(&[3]).as_deref(), Some("m_subTicks"));
+    }
+
+    #[test]
+    fn resolve_path_truncates_unknown_components() {
+        let pawn = pawn();
+
+        assert_eq!(pawn.resolve_path(&[]), None);
+        assert_eq!(pawn.resolve_path(&[9]), None);
+        assert_eq!(
+            pawn.resolve_path(&[1, 9]).as_deref(),
+            Some("CBodyComponent")
+        );
+        assert_eq!(pawn.resolve_path(&[0, 1]).as_deref(), Some("m_iHealth"));
+    }
+
+    #[test]
+    fn has_field_resolves_dotted_names() {
+        let pawn = pawn();
+
+        assert!(pawn.has_field("m_iHealth"));
+        assert!(pawn.has_field("CBodyComponent.m_cellX"));
+        assert!(pawn.has_field("m_pWeaponServices.m_hMyWeapons"));
+        assert!(!pawn.has_field("m_iHealth.m_cellX"));
+        assert!(!pawn.has_field("CBodyComponent.m_cellY"));
+        assert!(!pawn.has_field("CBody"));
+    }
+
+    #[test]
+    fn to_json_escapes_strings() {
+        let schema = class(
+            "C\"Quoted\\Class\"",
+            vec![field("line\nbreak\ttab\u{1}", false, None)],
+        );
+
+        let json = to_json([&schema]);
+
+        assert!(json.contains(r#""name": "C\"Quoted\\Class\"""#));
+        assert!(json.contains(r#""name": "line\nbreak\ttab\u0001""#));
+        assert!(json.contains(r#""encoder": null"#));
+        assert!(!json.contains('\u{1}'));
+    }
+
+    #[test]
+    fn to_json_writes_null_for_non_finite_ranges() {
+        let mut range = field("m_flRange", false, None);
+        range.low_value = Some(f32::NEG_INFINITY);
+        range.high_value = Some(2.5);
+        let schema = class("CRange", vec![range]);
+
+        let json = to_json([&schema]);
+
+        assert!(json.contains(r#""low_value": null, "high_value": 2.5"#));
+    }
+
+    #[test]
+    fn to_json_of_no_classes() {
+        assert_eq!(to_json([]), "[\n]\n");
+    }
+}
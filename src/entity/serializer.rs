@@ -8,9 +8,11 @@ use crate::{
         Reader,
         decoder::{serializer_derivation, skip_varint},
         field::FieldType,
+        serializer::dynamic::{DynamicField, Value},
     },
 };
 
+pub mod dynamic;
 pub mod vector;
 
 pub trait EntitySerializer: Send + Sync {
@@ -22,12 +24,111 @@ pub trait EntitySerializer: Send + Sync {
     ) -> Result<(), std::io::Error>;
 
     fn new_entity(&self) -> Box<dyn Any + Send + Sync>;
+
+    /// creates the field storage used by `DynamicEntity`,
+    /// None if the field can't be decoded dynamically
+    fn new_dynamic(&self) -> Option<Box<dyn DynamicField>> {
+        None
+    }
+
+    /// name of the type `decode` expects, only used for diagnostics
+    fn type_name(&self) -> &'static str {
+        "<unknown>"
+    }
+
+    /// checks if the field can be passed to `decode`
+    fn accepts(&self, field: &dyn Any) -> bool {
+        Any::type_id(self.new_entity().as_ref()) == Any::type_id(field)
+    }
+
+    /// index and serializer of a field of the nested class this serializer decodes,
+    /// None if there is no nested class or the field doesn't exist
+    fn nested_field(&self, _name: &str) -> Option<(u32, Arc<dyn EntitySerializer>)> {
+        None
+    }
+
+    /// decodes the element of a vector or array addressed by `path` into `element`,
+    /// `len` is the length of the field and is updated by length changes,
+    /// returns the index of the decoded element, None if only the length changed
+    fn decode_element(
+        &self,
+        _len: &mut usize,
+        _element: &mut dyn Any,
+        _path: &[u32],
+        _reader: &mut Reader<'_>,
+    ) -> Result<Option<usize>, std::io::Error> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Field is not a vector or an array",
+        ))
+    }
 }
 
 pub trait EntityClassSerializer: EntitySerializer {
     fn serializer_derivation(&self, field_type: &FieldType) -> Arc<dyn EntitySerializer>;
 
     fn clone_entity(&self, entity: &dyn Any) -> Result<Box<dyn Any + Send + Sync>, std::io::Error>;
+
+    /// problems found while binding the fields of a registered class
+    fn binding_errors(&self) -> &[BindingError] {
+        &[]
+    }
+}
+
+/// a field binding of a registered class which doesn't match the send tables
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BindingError {
+    /// the class is registered but not part of the send tables
+    MissingClass,
+    /// the bound field is not part of the send tables
+    MissingField { field_name: &'static str },
+    /// the type of the bound field doesn't match the type the serializer decodes into
+    TypeMismatch {
+        field_name: &'static str,
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// a dotted name binds a field below a field which is bound as a whole,
+    /// only the field bound as a whole is decoded
+    OverlappingField {
+        field_name: &'static str,
+        nested_field_name: &'static str,
+    },
+}
+
+impl BindingError {
+    /// the bound field, None for a missing class
+    pub fn field_name(&self) -> Option<&'static str> {
+        match self {
+            BindingError::MissingClass => None,
+            BindingError::MissingField { field_name }
+            | BindingError::TypeMismatch { field_name, .. }
+            | BindingError::OverlappingField { field_name, .. } => Some(field_name),
+        }
+    }
+}
+
+impl std::fmt::Display for BindingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BindingError::MissingClass => write!(f, "class is not part of the send tables"),
+            BindingError::MissingField { field_name } => {
+                write!(f, "field {field_name} is not part of the send tables")
+            }
+            BindingError::TypeMismatch {
+                field_name,
+                expected,
+                found,
+            } => write!(f, "field {field_name} is {found}, expected {expected}"),
+            BindingError::OverlappingField {
+                field_name,
+                nested_field_name,
+            } => write!(
+                f,
+                "field {nested_field_name} is shadowed by the binding of {field_name}"
+            ),
+        }
+    }
 }
 
 pub trait EntityField: Any + Send + Sync + Sized + Clone + 'static {
@@ -42,6 +143,12 @@ pub trait EntityField: Any + Send + Sync + Sized + Clone + 'static {
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self
     }
+
+    /// converts the field for dynamic access,
+    /// types without a dynamic representation are Null
+    fn to_value(&self) -> Value {
+        Value::Null
+    }
 }
 
 // implement EntityField for primitive types
@@ -50,6 +157,10 @@ impl EntityField for bool {
     fn new() -> Self {
         false
     }
+
+    fn to_value(&self) -> Value {
+        Value::Bool(*self)
+    }
 }
 
 impl EntityField for f32 {
@@ -57,6 +168,10 @@ impl EntityField for f32 {
     fn new() -> Self {
         0.0
     }
+
+    fn to_value(&self) -> Value {
+        Value::F32(*self)
+    }
 }
 
 impl EntityField for u64 {
@@ -64,6 +179,10 @@ impl EntityField for u64 {
     fn new() -> Self {
         0
     }
+
+    fn to_value(&self) -> Value {
+        Value::U64(*self)
+    }
 }
 
 impl EntityField for i64 {
@@ -71,6 +190,22 @@ impl EntityField for i64 {
     fn new() -> Self {
         0
     }
+
+    fn to_value(&self) -> Value {
+        Value::I64(*self)
+    }
+}
+
+/// index of the active type of a polymorphic field
+impl EntityField for usize {
+    #[inline(always)]
+    fn new() -> Self {
+        0
+    }
+
+    fn to_value(&self) -> Value {
+        Value::U64(*self as u64)
+    }
 }
 
 impl EntityField for String {
@@ -78,6 +213,10 @@ impl EntityField for String {
     fn new() -> Self {
         String::new()
     }
+
+    fn to_value(&self) -> Value {
+        Value::String(self.clone())
+    }
 }
 
 impl<T: EntityField> EntityField for Vec<T> {
@@ -85,6 +224,10 @@ impl<T: EntityField> EntityField for Vec<T> {
     fn new() -> Self {
         Vec::new()
     }
+
+    fn to_value(&self) -> Value {
+        Value::Array(self.iter().map(|v| v.to_value()).collect())
+    }
 }
 
 impl<T: EntityField> EntityField for Option<T> {
@@ -92,6 +235,10 @@ impl<T: EntityField> EntityField for Option<T> {
     fn new() -> Self {
         None
     }
+
+    fn to_value(&self) -> Value {
+        self.as_ref().map_or(Value::Null, |v| v.to_value())
+    }
 }
 
 impl EntityTypeWarp<f32> for u64 {
@@ -108,6 +255,25 @@ pub trait EntitySerializerTyped<T: EntityField>: Send + Sync {
         path: &[u32],
         reader: &mut Reader<'_>,
     ) -> Result<(), std::io::Error>;
+
+    /// see `EntitySerializer::nested_field`
+    fn nested_field(&self, _name: &str) -> Option<(u32, Arc<dyn EntitySerializer>)> {
+        None
+    }
+
+    /// see `EntitySerializer::decode_element`
+    fn decode_element(
+        &self,
+        _len: &mut usize,
+        _element: &mut dyn Any,
+        _path: &[u32],
+        _reader: &mut Reader<'_>,
+    ) -> Result<Option<usize>, std::io::Error> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Field is not a vector or an array",
+        ))
+    }
 }
 
 pub struct TypedEntitySerializerAdapter<S, T>
@@ -165,6 +331,32 @@ where
     fn new_entity(&self) -> Box<dyn Any + Send + Sync> {
         Box::new(T::new())
     }
+
+    fn new_dynamic(&self) -> Option<Box<dyn DynamicField>> {
+        Some(Box::new(T::new()))
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn accepts(&self, field: &dyn Any) -> bool {
+        field.is::<T>()
+    }
+
+    fn nested_field(&self, name: &str) -> Option<(u32, Arc<dyn EntitySerializer>)> {
+        self.inner.nested_field(name)
+    }
+
+    fn decode_element(
+        &self,
+        len: &mut usize,
+        element: &mut dyn Any,
+        path: &[u32],
+        reader: &mut Reader<'_>,
+    ) -> Result<Option<usize>, std::io::Error> {
+        self.inner.decode_element(len, element, path, reader)
+    }
 }
 
 pub trait EntityMultiComponents<T: EntityField, const N: usize>: EntityField {
@@ -340,6 +532,31 @@ where
 
         Ok(())
     }
+
+    fn decode_element(
+        &self,
+        len: &mut usize,
+        element: &mut dyn Any,
+        path: &[u32],
+        reader: &mut Reader<'_>,
+    ) -> Result<Option<usize>, std::io::Error> {
+        if path.is_empty() {
+            *len = reader.read_varint_u64()? as usize;
+            return Ok(None);
+        }
+
+        let idx = path[0] as usize;
+        if idx >= *len {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Invalid vector index",
+            ));
+        }
+
+        self.inner
+            .decode_typed(element.downcast_mut::<T>(), &path[1..], reader)?;
+        Ok(Some(idx))
+    }
 }
 
 /// deserializes a fixed-size array
@@ -403,6 +620,33 @@ where
             self.inner.decode_typed(None, &path[1..], reader)
         }
     }
+
+    fn decode_element(
+        &self,
+        len: &mut usize,
+        element: &mut dyn Any,
+        path: &[u32],
+        reader: &mut Reader<'_>,
+    ) -> Result<Option<usize>, std::io::Error> {
+        *len = self.size;
+
+        let Some(&idx) = path.first() else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Empty path is not allowed for ArraySerializer",
+            ));
+        };
+        if idx as usize >= self.size {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Invalid array index",
+            ));
+        }
+
+        self.inner
+            .decode_typed(element.downcast_mut::<T>(), &path[1..], reader)?;
+        Ok(Some(idx as usize))
+    }
 }
 
 /// deserializes a pointer type
@@ -460,6 +704,11 @@ where
 
         Ok(())
     }
+
+    /// fields of the pointee, the presence of the pointer is not tracked
+    fn nested_field(&self, name: &str) -> Option<(u32, Arc<dyn EntitySerializer>)> {
+        self.inner.nested_field(name)
+    }
 }
 
 #[derive(Clone)]
@@ -473,6 +722,8 @@ impl EntityField for UnknownEntity {
 
 #[derive(Clone)]
 pub struct UnknownEntitySerializer {
+    /// kept for binding nested fields by name
+    names: Arc<[Box<str>]>,
     serializers: Box<[Arc<dyn EntitySerializer>]>,
 }
 
@@ -481,11 +732,22 @@ impl UnknownEntitySerializer {
         serializers: Vec<(&str, Arc<dyn EntitySerializer>)>,
     ) -> Arc<dyn EntityClassSerializer> {
         Arc::new(Self {
+            names: serializers.iter().map(|(n, _)| Box::from(*n)).collect(),
             serializers: serializers.into_iter().map(|(_, s)| s).collect(),
         })
     }
 }
 
+/// looks up a field of a class serializer by name
+fn find_field(
+    names: &[Box<str>],
+    serializers: impl Fn(usize) -> Arc<dyn EntitySerializer>,
+    name: &str,
+) -> Option<(u32, Arc<dyn EntitySerializer>)> {
+    let idx = names.iter().position(|n| n.as_ref() == name)?;
+    Some((idx as u32, serializers(idx)))
+}
+
 impl EntitySerializerTyped<UnknownEntity> for UnknownEntitySerializer {
     fn decode_typed(
         &self,
@@ -514,6 +776,10 @@ impl EntitySerializerTyped<UnknownEntity> for UnknownEntitySerializer {
             ))
         }
     }
+
+    fn nested_field(&self, name: &str) -> Option<(u32, Arc<dyn EntitySerializer>)> {
+        find_field(&self.names, |idx| self.serializers[idx].clone(), name)
+    }
 }
 
 impl EntitySerializer for UnknownEntitySerializer {
@@ -529,6 +795,15 @@ impl EntitySerializer for UnknownEntitySerializer {
     fn new_entity(&self) -> Box<dyn Any + Send + Sync> {
         Box::new(UnknownEntity)
     }
+
+    /// nested classes have to be registered to be bound
+    fn type_name(&self) -> &'static str {
+        "UnknownEntity (unregistered class)"
+    }
+
+    fn nested_field(&self, name: &str) -> Option<(u32, Arc<dyn EntitySerializer>)> {
+        EntitySerializerTyped::nested_field(self, name)
+    }
 }
 
 impl EntityClassSerializer for UnknownEntitySerializer {
@@ -548,19 +823,103 @@ impl EntityClassSerializer for UnknownEntitySerializer {
     }
 }
 
+/// a polymorphic pointer, e.g. `m_pGameModeRules`
+/// which points to one of the serializers listed in `FieldSchema::polymorphic_types`
+#[derive(Default)]
 pub struct PolymorphicEntity {
-    pub index: usize,
-    pub item: Box<dyn Any + Send + Sync>,
+    /// index into the polymorphic types of the field, None if the pointer is null
+    pub index: Option<usize>,
+    /// network name of the active type
+    pub class_name: Option<Arc<str>>,
+    /// the active type, decoded like a nested field of that class
+    pub item: Option<Box<dyn DynamicField>>,
+}
+
+impl PolymorphicEntity {
+    pub fn is_null(&self) -> bool {
+        self.index.is_none()
+    }
+
+    /// returns the active type if it is decoded as T
+    pub fn get<T: EntityField>(&self) -> Option<&T> {
+        self.item.as_ref()?.as_any().downcast_ref::<T>()
+    }
+
+    pub fn get_mut<T: EntityField>(&mut self) -> Option<&mut T> {
+        self.item.as_mut()?.as_any_mut().downcast_mut::<T>()
+    }
+}
+
+impl Clone for PolymorphicEntity {
+    fn clone(&self) -> Self {
+        Self {
+            index: self.index,
+            class_name: self.class_name.clone(),
+            item: self.item.as_ref().map(|item| item.clone_field()),
+        }
+    }
 }
 
+impl EntityField for PolymorphicEntity {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn to_value(&self) -> Value {
+        self.item
+            .as_ref()
+            .map_or(Value::Null, |item| item.to_value())
+    }
+}
+
+/// network name and serializer of every type a polymorphic pointer can take
+pub type PolymorphicTypes = Box<[(Arc<str>, Arc<dyn EntitySerializer>)]>;
+
+/// decodes a polymorphic pointer into a `PolymorphicEntity`
+///
+/// a bare `usize` only tracks the index of the active type, the pointer stays
+/// at the last index once it is cleared. either is required to decode the field
+/// as the fields of the active type depend on it
 pub struct PolymorphicSerializer {
-    serializers: Box<[Arc<dyn EntitySerializer>]>,
+    serializers: PolymorphicTypes,
 }
 
 impl PolymorphicSerializer {
-    pub fn new(serializers: Box<[Arc<dyn EntitySerializer>]>) -> Self {
+    pub fn new(serializers: PolymorphicTypes) -> Self {
         Self { serializers }
     }
+
+    /// reads the presence bit and the index of the active type, None if the pointer is null
+    fn read_index(&self, reader: &mut Reader<'_>) -> Result<Option<usize>, std::io::Error> {
+        if !reader.read_bit()? {
+            return Ok(None);
+        }
+
+        let idx = reader.read_ubit_int()? as usize;
+        if idx >= self.serializers.len() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "Unknown polymorphic serializer index: {} (max: {})",
+                    idx,
+                    self.serializers.len() - 1
+                ),
+            ));
+        }
+
+        Ok(Some(idx))
+    }
+
+    fn serializer(&self, idx: Option<usize>) -> Result<&Arc<dyn EntitySerializer>, std::io::Error> {
+        idx.and_then(|idx| self.serializers.get(idx))
+            .map(|(_, serializer)| serializer)
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "Field of a null or unknown polymorphic pointer",
+                )
+            })
+    }
 }
 
 impl EntitySerializer for PolymorphicSerializer {
@@ -577,38 +936,28 @@ impl EntitySerializer for PolymorphicSerializer {
             ));
         };
 
-        // TODO: validate the code
-
         if let Some(e) = e.downcast_mut::<PolymorphicEntity>() {
             if path.is_empty() {
-                let _present = reader.read_bit()?;
-                let serializer_idx = reader.read_ubit_int()? as usize;
-                e.item = if serializer_idx < self.serializers.len() {
-                    let serializer = &self.serializers[serializer_idx];
-                    e.index = serializer_idx;
-                    serializer.new_entity()
-                } else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!(
-                            "Unknown polymorphic serializer index: {} (max: {})",
-                            serializer_idx,
-                            self.serializers.len() - 1
-                        ),
-                    ));
-                };
+                let idx = self.read_index(reader)?;
+
+                // keep the decoded fields if the type didn't change
+                if idx != e.index || (idx.is_some() && e.item.is_none()) {
+                    e.index = idx;
+                    e.class_name = idx.map(|idx| self.serializers[idx].0.clone());
+                    e.item = idx.and_then(|idx| self.serializers[idx].1.new_dynamic());
+                }
             } else {
-                let serializer = &self.serializers[e.index];
-                serializer.decode(Some(&mut *e.item), path, reader)?;
+                let serializer = self.serializer(e.index)?;
+                let item = e.item.as_mut().map(|item| item.as_any_mut());
+                serializer.decode(item, path, reader)?;
             }
         } else if let Some(e) = e.downcast_mut::<usize>() {
             if path.is_empty() {
-                let _present = reader.read_bit()?;
-                let serializer_idx = reader.read_ubit_int()? as usize;
-                *e = serializer_idx;
+                if let Some(idx) = self.read_index(reader)? {
+                    *e = idx;
+                }
             } else {
-                let serializer = &self.serializers[*e];
-                serializer.decode(None, path, reader)?;
+                self.serializer(Some(*e))?.decode(None, path, reader)?;
             }
         } else {
             return Err(std::io::Error::new(
@@ -621,7 +970,19 @@ impl EntitySerializer for PolymorphicSerializer {
     }
 
     fn new_entity(&self) -> Box<dyn Any + Send + Sync> {
-        self.serializers[0].new_entity()
+        Box::new(PolymorphicEntity::default())
+    }
+
+    fn new_dynamic(&self) -> Option<Box<dyn DynamicField>> {
+        Some(Box::new(PolymorphicEntity::default()))
+    }
+
+    fn type_name(&self) -> &'static str {
+        "PolymorphicEntity or usize"
+    }
+
+    fn accepts(&self, field: &dyn Any) -> bool {
+        field.is::<PolymorphicEntity>() || field.is::<usize>()
     }
 }
 
@@ -635,18 +996,391 @@ pub type CustomEntitySerializerType<T> = Box<
     )],
 >;
 
+/// a field of a registered class, generated by the `EntityClass` derive
+pub struct FieldBinding<T> {
+    /// name of the field in the send tables
+    pub name: &'static str,
+    /// name of the Rust type of the field, only used for diagnostics
+    pub type_name: &'static str,
+    pub getter: fn(&mut T) -> &mut dyn Any,
+    #[allow(clippy::type_complexity)]
+    pub on_changed: Option<fn(&mut T) -> Result<(), std::io::Error>>,
+    /// stores decoded integers into the field, used by enums, narrowed integers and handles
+    pub from_int: Option<IntConversion>,
+}
+
+/// stores decoded integers into a field of another type
+#[derive(Clone, Copy)]
+pub enum IntConversion {
+    Scalar(fn(i128, &mut dyn Any)),
+    /// element-wise for vectors and arrays
+    Vec(fn(&mut dyn Any) -> Option<&mut dyn IntVec>),
+}
+
+/// field types stored from decoded integers, e.g. narrowed integers and `EntityHandle`
+pub trait FromEntityInt: Clone + Default + 'static {
+    fn from_entity_int(value: i128) -> Self;
+}
+
+macro_rules! narrow_int {
+    ($($type:ty),*) => {
+        $(
+            impl FromEntityInt for $type {
+                /// truncating
+                #[inline(always)]
+                fn from_entity_int(value: i128) -> Self {
+                    value as $type
+                }
+            }
+        )*
+    };
+}
+
+narrow_int!(u8, u16, u32, i8, i16, i32);
+
+/// `IntConversion::Scalar` for a `FromEntityInt` field
+pub fn convert_int<T: FromEntityInt>(value: i128, field: &mut dyn Any) {
+    if let Some(field) = field.downcast_mut::<T>() {
+        *field = T::from_entity_int(value);
+    }
+}
+
+/// `IntConversion::Vec` for a vector of `FromEntityInt`
+pub fn convert_int_vec<T: FromEntityInt>(field: &mut dyn Any) -> Option<&mut dyn IntVec> {
+    field
+        .downcast_mut::<Vec<T>>()
+        .map(|field| field as &mut dyn IntVec)
+}
+
+/// a vector field converted element-wise from integers
+pub trait IntVec {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn resize(&mut self, len: usize);
+
+    fn set(&mut self, idx: usize, value: i128);
+}
+
+impl<T: FromEntityInt> IntVec for Vec<T> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn resize(&mut self, len: usize) {
+        Vec::resize(self, len, T::default());
+    }
+
+    fn set(&mut self, idx: usize, value: i128) {
+        self[idx] = T::from_entity_int(value);
+    }
+}
+
+/// decodes an integer field into a temporary and converts it into the bound field
+struct IntConversionSerializer {
+    inner: Arc<dyn EntitySerializer>,
+    signed: bool,
+    conversion: IntConversion,
+    type_name: &'static str,
+}
+
+impl IntConversionSerializer {
+    /// None if the field isn't decoded as an integer
+    fn new(
+        inner: Arc<dyn EntitySerializer>,
+        conversion: IntConversion,
+        type_name: &'static str,
+    ) -> Option<Self> {
+        let (unsigned, signed) = match conversion {
+            IntConversion::Scalar(_) => (inner.accepts(&0u64), inner.accepts(&0i64)),
+            IntConversion::Vec(_) => (
+                inner.accepts(&Vec::<u64>::new()),
+                inner.accepts(&Vec::<i64>::new()),
+            ),
+        };
+
+        if !unsigned && !signed {
+            return None;
+        }
+
+        Some(Self {
+            inner,
+            signed,
+            conversion,
+            type_name,
+        })
+    }
+
+    fn decode_as<I>(
+        &self,
+        e: &mut dyn Any,
+        path: &[u32],
+        reader: &mut Reader<'_>,
+    ) -> Result<(), std::io::Error>
+    where
+        I: EntityField + Copy + Default + Into<i128>,
+    {
+        match self.conversion {
+            IntConversion::Scalar(convert) => {
+                let mut value = I::default();
+                self.inner.decode(Some(&mut value), path, reader)?;
+                convert(value.into(), e);
+            }
+            IntConversion::Vec(elements) => {
+                let Some(e) = elements(e) else {
+                    return self.inner.decode(None, path, reader);
+                };
+
+                let mut len = e.len();
+                let mut value = I::default();
+                let idx = self
+                    .inner
+                    .decode_element(&mut len, &mut value, path, reader)?;
+
+                if len != e.len() {
+                    e.resize(len);
+                }
+                if let Some(idx) = idx {
+                    e.set(idx, value.into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl EntitySerializer for IntConversionSerializer {
+    fn decode(
+        &self,
+        entity: Option<&mut dyn Any>,
+        path: &[u32],
+        reader: &mut Reader<'_>,
+    ) -> Result<(), std::io::Error> {
+        let Some(e) = entity else {
+            return self.inner.decode(None, path, reader);
+        };
+
+        if self.signed {
+            self.decode_as::<i64>(e, path, reader)
+        } else {
+            self.decode_as::<u64>(e, path, reader)
+        }
+    }
+
+    fn new_entity(&self) -> Box<dyn Any + Send + Sync> {
+        self.inner.new_entity()
+    }
+
+    fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// the conversion ignores fields of other types
+    fn accepts(&self, _field: &dyn Any) -> bool {
+        true
+    }
+}
+
+/// projects a class onto one of its fields, used by `#[entity(flatten)]`
+pub trait FieldAccessor: 'static {
+    type Outer: 'static;
+    type Inner: 'static;
+
+    fn get(outer: &mut Self::Outer) -> &mut Self::Inner;
+}
+
+/// accessor of a class to itself
+pub struct IdentityAccessor<T>(std::marker::PhantomData<T>);
+
+impl<T: 'static> FieldAccessor for IdentityAccessor<T> {
+    type Outer = T;
+    type Inner = T;
+
+    #[inline(always)]
+    fn get(outer: &mut T) -> &mut T {
+        outer
+    }
+}
+
+/// chains two accessors, used by flattened fields of flattened classes
+impl<A, B> FieldAccessor for (A, B)
+where
+    A: FieldAccessor,
+    B: FieldAccessor<Outer = A::Inner>,
+{
+    type Outer = A::Outer;
+    type Inner = B::Inner;
+
+    #[inline(always)]
+    fn get(outer: &mut Self::Outer) -> &mut Self::Inner {
+        B::get(A::get(outer))
+    }
+}
+
+/// field bindings of a class, generated by the `EntityClass` derive
+pub trait EntityClassBindings: EntityField {
+    /// bindings of every field including flattened ones,
+    /// reached from the outer class through the accessor
+    fn bindings<A: FieldAccessor<Inner = Self>>() -> Vec<FieldBinding<A::Outer>>;
+}
+
+/// a binding to a field of a nested class, e.g. `m_pInGameMoneyServices.m_iAccount`
+#[derive(Clone)]
+struct NestedFieldBinding<T> {
+    /// path below the top-level field
+    path: Box<[u32]>,
+    serializer: Arc<dyn EntitySerializer>,
+    getter: fn(&mut T) -> &mut dyn Any,
+    #[allow(clippy::type_complexity)]
+    on_changed: Option<fn(&mut T) -> Result<(), std::io::Error>>,
+}
+
 #[derive(Clone)]
 pub struct CustomEntitySerializer<T: EntityField> {
     #[allow(clippy::type_complexity)]
     serializers: CustomEntitySerializerType<T>,
+    /// kept for binding nested fields by name, empty if created with `new`
+    names: Arc<[Box<str>]>,
+    /// nested bindings indexed by their top-level field
+    nested: Box<[Box<[NestedFieldBinding<T>]>]>,
+    binding_errors: Box<[BindingError]>,
 }
 
 impl<T: EntityField> CustomEntitySerializer<T> {
     pub fn new(serializers: CustomEntitySerializerType<T>) -> Self {
-        Self { serializers }
+        Self {
+            nested: serializers.iter().map(|_| Box::default()).collect(),
+            serializers,
+            names: Arc::default(),
+            binding_errors: Box::new([]),
+        }
+    }
+
+    /// binds the fields of the send tables by name,
+    /// bindings which are missing or have the wrong type are reported as `BindingError`
+    ///
+    /// dotted names bind fields of nested classes, e.g. `m_pInGameMoneyServices.m_iAccount`.
+    /// the field keeps its last value if the pointer to the nested class is cleared
+    pub fn from_bindings(
+        serializers: Vec<(&str, Arc<dyn EntitySerializer>)>,
+        bindings: &[FieldBinding<T>],
+    ) -> Self {
+        let mut entity = T::new();
+        let mut binding_errors = Vec::new();
+
+        let mut fields = serializers
+            .iter()
+            .map(|(_, serializer)| (serializer.clone(), None, None))
+            .collect::<Vec<_>>();
+        let mut nested = serializers.iter().map(|_| Vec::new()).collect::<Vec<_>>();
+        // names of the bindings, to report overlaps of top-level and nested bindings
+        let mut field_names = serializers.iter().map(|_| None).collect::<Vec<_>>();
+        let mut nested_names = serializers.iter().map(|_| Vec::new()).collect::<Vec<_>>();
+
+        for binding in bindings {
+            let (idx, path, mut serializer) =
+                if let Some(idx) = serializers.iter().position(|(n, _)| *n == binding.name) {
+                    (idx, None, serializers[idx].1.clone())
+                } else if let Some((idx, path, serializer)) =
+                    resolve_nested_field(&serializers, binding.name)
+                {
+                    (idx, Some(path), serializer)
+                } else {
+                    binding_errors.push(BindingError::MissingField {
+                        field_name: binding.name,
+                    });
+                    continue;
+                };
+
+            if let Some(conversion) = binding.from_int {
+                let Some(converted) =
+                    IntConversionSerializer::new(serializer.clone(), conversion, binding.type_name)
+                else {
+                    binding_errors.push(BindingError::TypeMismatch {
+                        field_name: binding.name,
+                        expected: serializer.type_name(),
+                        found: binding.type_name,
+                    });
+                    continue;
+                };
+
+                serializer = Arc::new(converted);
+            }
+
+            // decoding into a mismatched field fails, skip it instead
+            if !serializer.accepts((binding.getter)(&mut entity)) {
+                binding_errors.push(BindingError::TypeMismatch {
+                    field_name: binding.name,
+                    expected: serializer.type_name(),
+                    found: binding.type_name,
+                });
+                continue;
+            }
+
+            match path {
+                Some(path) => {
+                    nested_names[idx].push(binding.name);
+                    nested[idx].push(NestedFieldBinding {
+                        path: path.into_boxed_slice(),
+                        serializer,
+                        getter: binding.getter,
+                        on_changed: binding.on_changed,
+                    });
+                }
+                None => {
+                    field_names[idx] = Some(binding.name);
+                    fields[idx] = (serializer, Some(binding.getter), binding.on_changed);
+                }
+            }
+        }
+
+        // the top-level getter takes the whole field, the nested bindings would never decode
+        for (idx, field_name) in field_names.into_iter().enumerate() {
+            let Some(field_name) = field_name else {
+                continue;
+            };
+
+            for nested_field_name in nested_names[idx].drain(..) {
+                binding_errors.push(BindingError::OverlappingField {
+                    field_name,
+                    nested_field_name,
+                });
+            }
+            nested[idx].clear();
+        }
+
+        Self {
+            serializers: fields.into_boxed_slice(),
+            names: serializers.iter().map(|(n, _)| Box::from(*n)).collect(),
+            nested: nested.into_iter().map(Vec::into_boxed_slice).collect(),
+            binding_errors: binding_errors.into_boxed_slice(),
+        }
     }
 }
 
+/// resolves a dotted name to its top-level field, the path below it and the serializer of the field
+fn resolve_nested_field(
+    serializers: &[(&str, Arc<dyn EntitySerializer>)],
+    name: &str,
+) -> Option<(usize, Vec<u32>, Arc<dyn EntitySerializer>)> {
+    let (head, rest) = name.split_once('.')?;
+    let idx = serializers.iter().position(|(n, _)| *n == head)?;
+
+    let mut serializer = serializers[idx].1.clone();
+    let mut path = Vec::new();
+    for component in rest.split('.') {
+        let (field_idx, field_serializer) = serializer.nested_field(component)?;
+        path.push(field_idx);
+        serializer = field_serializer;
+    }
+
+    Some((idx, path, serializer))
+}
+
 impl<T: EntityField> EntitySerializer for CustomEntitySerializer<T> {
     fn decode(
         &self,
@@ -671,6 +1405,14 @@ impl<T: EntityField> EntitySerializer for CustomEntitySerializer<T> {
     fn new_entity(&self) -> Box<dyn Any + Send + Sync> {
         Box::new(T::new())
     }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn nested_field(&self, name: &str) -> Option<(u32, Arc<dyn EntitySerializer>)> {
+        EntitySerializerTyped::nested_field(self, name)
+    }
 }
 
 impl<T: EntityField> EntitySerializerTyped<T> for CustomEntitySerializer<T> {
@@ -709,6 +1451,18 @@ impl<T: EntityField> EntitySerializerTyped<T> for CustomEntitySerializer<T> {
                 if let Some(callback) = callback {
                     callback(e)?;
                 }
+            } else if let Some(binding) = self.nested[idx]
+                .iter()
+                .find(|b| path[1..].starts_with(&b.path))
+            {
+                let field = (binding.getter)(e);
+                binding
+                    .serializer
+                    .decode(Some(field), &path[1 + binding.path.len()..], reader)?;
+
+                if let Some(callback) = binding.on_changed {
+                    callback(e)?;
+                }
             } else {
                 serializer.decode(None, &path[1..], reader)?;
             }
@@ -718,6 +1472,10 @@ impl<T: EntityField> EntitySerializerTyped<T> for CustomEntitySerializer<T> {
 
         Ok(())
     }
+
+    fn nested_field(&self, name: &str) -> Option<(u32, Arc<dyn EntitySerializer>)> {
+        find_field(&self.names, |idx| self.serializers[idx].0.clone(), name)
+    }
 }
 
 impl<T: EntityField> EntityClassSerializer for CustomEntitySerializer<T> {
@@ -735,4 +1493,8 @@ impl<T: EntityField> EntityClassSerializer for CustomEntitySerializer<T> {
             ))
         }
     }
+
+    fn binding_errors(&self) -> &[BindingError] {
+        &self.binding_errors
+    }
 }
@@ -0,0 +1,344 @@
+use std::{any::Any, sync::Arc};
+
+use crate::entity::{
+    Reader,
+    decoder::serializer_derivation,
+    field::FieldType,
+    serializer::{
+        EntityClassSerializer, EntityField, EntitySerializer, EntitySerializerTyped,
+        vector::{QAngle, Transform6, Vector2, Vector3, Vector4},
+    },
+};
+
+/// a decoded property of a dynamic entity
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// empty pointers and fields without a dynamic representation
+    Null,
+    Bool(bool),
+    F32(f32),
+    U64(u64),
+    I64(i64),
+    String(String),
+    Vector2(Vector2),
+    Vector3(Vector3),
+    Vector4(Vector4),
+    QAngle(QAngle),
+    Transform6(Transform6),
+    /// vectors and fixed-size arrays
+    Array(Vec<Value>),
+    /// nested serializers, fields are in send table order
+    Object(Vec<(Arc<str>, Value)>),
+}
+
+impl Value {
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            Value::F32(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// integers of either sign, None if the value does not fit
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::U64(v) => Some(*v),
+            Value::I64(v) => u64::try_from(*v).ok(),
+            _ => None,
+        }
+    }
+
+    /// integers of either sign, None if the value does not fit
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::I64(v) => Some(*v),
+            Value::U64(v) => i64::try_from(*v).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// looks up a field of an object by name
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Object(fields) => fields
+                .iter()
+                .find(|(field_name, _)| field_name.as_ref() == name)
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+/// type-erased field storage of a dynamic entity,
+/// implemented for every `EntityField`
+pub trait DynamicField: Any + Send + Sync {
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn clone_field(&self) -> Box<dyn DynamicField>;
+
+    fn to_value(&self) -> Value;
+}
+
+impl<T: EntityField> DynamicField for T {
+    #[inline(always)]
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    #[inline(always)]
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_field(&self) -> Box<dyn DynamicField> {
+        Box::new(self.clone())
+    }
+
+    fn to_value(&self) -> Value {
+        EntityField::to_value(self)
+    }
+}
+
+/// an entity decoded without a derived struct,
+/// properties are looked up by their send table names
+pub struct DynamicEntity {
+    names: Arc<[Arc<str>]>,
+    /// None for fields that can't be decoded dynamically
+    fields: Vec<Option<Box<dyn DynamicField>>>,
+}
+
+impl DynamicEntity {
+    /// returns the value of a field,
+    /// fields of nested serializers can be reached with dotted names
+    /// e.g. `CBodyComponent.m_cellX`
+    pub fn get(&self, name: &str) -> Option<Value> {
+        if let Some(field) = self.field(name) {
+            return Some(field.to_value());
+        }
+
+        let (head, rest) = name.split_once('.')?;
+        let field = self.field(head)?.as_any();
+        let nested = field.downcast_ref::<DynamicEntity>().or_else(|| {
+            field
+                .downcast_ref::<Option<DynamicEntity>>()
+                .and_then(|v| v.as_ref())
+        })?;
+
+        nested.get(rest)
+    }
+
+    fn field(&self, name: &str) -> Option<&dyn DynamicField> {
+        let idx = self.names.iter().position(|n| n.as_ref() == name)?;
+        self.fields[idx].as_deref()
+    }
+
+    /// names of the top-level fields in send table order
+    pub fn field_names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(|n| n.as_ref())
+    }
+
+    /// iterates over all top-level fields in send table order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Value)> {
+        self.names
+            .iter()
+            .zip(self.fields.iter())
+            .map(|(name, field)| {
+                (
+                    name.as_ref(),
+                    field.as_ref().map_or(Value::Null, |f| f.to_value()),
+                )
+            })
+    }
+}
+
+impl Clone for DynamicEntity {
+    fn clone(&self) -> Self {
+        Self {
+            names: self.names.clone(),
+            fields: self
+                .fields
+                .iter()
+                .map(|f| f.as_ref().map(|f| f.clone_field()))
+                .collect(),
+        }
+    }
+}
+
+impl EntityField for DynamicEntity {
+    /// an empty entity, fields are populated by the serializer on first decode
+    fn new() -> Self {
+        Self {
+            names: Arc::default(),
+            fields: Vec::new(),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Object(
+            self.names
+                .iter()
+                .cloned()
+                .zip(
+                    self.fields
+                        .iter()
+                        .map(|f| f.as_ref().map_or(Value::Null, |f| f.to_value())),
+                )
+                .collect(),
+        )
+    }
+}
+
+/// decodes every field of a class into a `DynamicEntity`
+///
+/// register it for chosen classes with `register_entity_serializer`
+/// or for all classes with `set_default_entity_serializer`,
+/// nested serializers are only decoded if their classes are dynamic as well
+#[derive(Clone)]
+pub struct DynamicEntitySerializer {
+    names: Arc<[Arc<str>]>,
+    serializers: Box<[Arc<dyn EntitySerializer>]>,
+}
+
+impl DynamicEntitySerializer {
+    pub fn new_serializer(
+        serializers: Vec<(&str, Arc<dyn EntitySerializer>)>,
+    ) -> Arc<dyn EntityClassSerializer> {
+        Arc::new(Self {
+            names: serializers.iter().map(|(n, _)| Arc::from(*n)).collect(),
+            serializers: serializers.into_iter().map(|(_, s)| s).collect(),
+        })
+    }
+
+    fn new_dynamic_entity(&self) -> DynamicEntity {
+        DynamicEntity {
+            names: self.names.clone(),
+            fields: self.serializers.iter().map(|s| s.new_dynamic()).collect(),
+        }
+    }
+}
+
+impl EntitySerializerTyped<DynamicEntity> for DynamicEntitySerializer {
+    fn decode_typed(
+        &self,
+        entity: Option<&mut DynamicEntity>,
+        path: &[u32],
+        reader: &mut Reader<'_>,
+    ) -> Result<(), std::io::Error> {
+        if path.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Empty path is not allowed for DynamicEntitySerializer",
+            ));
+        }
+
+        let idx = path[0] as usize;
+        if idx >= self.serializers.len() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "Invalid serializer index: {} (max: {})",
+                    idx,
+                    self.serializers.len() - 1,
+                ),
+            ));
+        }
+
+        let serializer = &self.serializers[idx];
+
+        if let Some(e) = entity {
+            // entities created by vector or pointer serializers start empty
+            if e.fields.is_empty() {
+                *e = self.new_dynamic_entity();
+            }
+
+            if let Some(field) = e.fields[idx].as_mut() {
+                return serializer.decode(Some(field.as_any_mut()), &path[1..], reader);
+            }
+        }
+
+        serializer.decode(None, &path[1..], reader)
+    }
+
+    fn nested_field(&self, name: &str) -> Option<(u32, Arc<dyn EntitySerializer>)> {
+        let idx = self.names.iter().position(|n| n.as_ref() == name)?;
+        Some((idx as u32, self.serializers[idx].clone()))
+    }
+}
+
+impl EntitySerializer for DynamicEntitySerializer {
+    fn decode(
+        &self,
+        entity: Option<&mut dyn Any>,
+        path: &[u32],
+        reader: &mut Reader<'_>,
+    ) -> Result<(), std::io::Error> {
+        if let Some(e) = entity {
+            if let Some(e) = e.downcast_mut::<DynamicEntity>() {
+                self.decode_typed(Some(e), path, reader)
+            } else {
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "Entity type mismatch in DynamicEntitySerializer",
+                ))
+            }
+        } else {
+            self.decode_typed(None, path, reader)
+        }
+    }
+
+    fn new_entity(&self) -> Box<dyn Any + Send + Sync> {
+        Box::new(self.new_dynamic_entity())
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<DynamicEntity>()
+    }
+
+    fn nested_field(&self, name: &str) -> Option<(u32, Arc<dyn EntitySerializer>)> {
+        EntitySerializerTyped::nested_field(self, name)
+    }
+}
+
+impl EntityClassSerializer for DynamicEntitySerializer {
+    fn serializer_derivation(&self, field_type: &FieldType) -> Arc<dyn EntitySerializer> {
+        serializer_derivation(self.clone(), field_type)
+    }
+
+    fn clone_entity(&self, entity: &dyn Any) -> Result<Box<dyn Any + Send + Sync>, std::io::Error> {
+        if let Some(e) = entity.downcast_ref::<DynamicEntity>() {
+            Ok(Box::new(e.clone()))
+        } else {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Entity type mismatch in DynamicEntitySerializer",
+            ))
+        }
+    }
+}
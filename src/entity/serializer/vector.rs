@@ -1,8 +1,10 @@
-use crate::entity::{serializer::EntityField, serializer::EntityMultiComponents};
+use crate::entity::{
+    serializer::EntityField, serializer::EntityMultiComponents, serializer::dynamic::Value,
+};
 
 macro_rules! vector {
     ($name:ident, $($v: ident),+) => {
-        #[derive(Debug, Clone, Default)]
+        #[derive(Debug, Clone, Default, PartialEq)]
         #[repr(C)]
         pub struct $name {
             $(pub $v: f32),+
@@ -15,6 +17,10 @@ macro_rules! vector {
                     $($v: 0.0),+
                 }
             }
+
+            fn to_value(&self) -> Value {
+                Value::$name(self.clone())
+            }
         }
 
         impl EntityMultiComponents<f32, {vector!(@count $($v),+)}> for $name {
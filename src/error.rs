@@ -0,0 +1,280 @@
+use std::{fmt, sync::Arc};
+
+use crate::entity::serializer::BindingError;
+
+/// where in the demo an error occurred,
+/// filled in while the error propagates up to `read_frame`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// tick of the frame being handled
+    pub tick: Option<u32>,
+    /// demo command of the frame being handled, without the compression flag
+    pub command: Option<i32>,
+    /// type of the net message being handled inside a packet
+    pub message_type: Option<u32>,
+    /// entity class being decoded or built from the send tables
+    pub entity_class: Option<Arc<str>>,
+    /// field of the entity class, the top-level field for decoded field paths
+    pub field: Option<Arc<str>>,
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(tick) = self.tick {
+            write!(f, " at tick {tick}")?;
+        }
+        if let Some(command) = self.command {
+            write!(f, ", command {command}")?;
+        }
+        if let Some(message_type) = self.message_type {
+            write!(f, ", message type {message_type}")?;
+        }
+        if let Some(entity_class) = &self.entity_class {
+            write!(f, " in class {entity_class}")?;
+        }
+        if let Some(field) = &self.field {
+            write!(f, ", field {field}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ParserError {
+    /// the underlying reader failed
+    Io(std::io::Error),
+    /// the demo ended in the middle of a frame,
+    /// usually caused by an incomplete download or a crashed recording
+    Truncated { context: ErrorContext },
+    /// a frame, message or string table could not be decompressed
+    Decompression {
+        context: ErrorContext,
+        message: String,
+    },
+    /// a protobuf message could not be decoded
+    MessageDecode {
+        context: ErrorContext,
+        source: prost::DecodeError,
+    },
+    /// the demo is malformed, e.g. required values are missing
+    InvalidData {
+        context: ErrorContext,
+        message: String,
+    },
+    /// packet entities referenced a class id missing from the class info
+    UnknownClassId {
+        context: ErrorContext,
+        class_id: u32,
+    },
+    /// the class info referenced a class missing from the send tables
+    UnknownSerializer {
+        context: ErrorContext,
+        class_name: String,
+    },
+    /// the send tables contain a field the parser does not know how to decode,
+    /// usually caused by a game update, the class and the field are part of the context
+    UnsupportedEncoder {
+        context: ErrorContext,
+        var_type: String,
+        encoder: Option<String>,
+        source: std::io::Error,
+    },
+    /// an entity field could not be decoded, the class and the top-level field of the path
+    /// are part of the context
+    FieldDecode {
+        context: ErrorContext,
+        path: Vec<u32>,
+        source: std::io::Error,
+    },
+    /// registered entity classes don't match the send tables,
+    /// only returned if strict entity bindings are enabled
+    InvalidBindings {
+        context: ErrorContext,
+        /// class name and binding error
+        errors: Vec<(String, BindingError)>,
+    },
+    /// an event listener returned an error
+    Listener(std::io::Error),
+    /// the parser was used incorrectly, e.g. registering after parsing has started
+    InvalidInput(String),
+}
+
+impl ParserError {
+    pub fn invalid_data(message: impl Into<String>) -> Self {
+        ParserError::InvalidData {
+            context: ErrorContext::default(),
+            message: message.into(),
+        }
+    }
+
+    /// maps errors of reading frames from the demo, the demo is truncated if it ends early
+    pub(crate) fn frame_read(err: std::io::Error) -> Self {
+        if err.kind() == std::io::ErrorKind::UnexpectedEof {
+            ParserError::Truncated {
+                context: ErrorContext::default(),
+            }
+        } else {
+            ParserError::Io(err)
+        }
+    }
+
+    pub fn decompression(message: impl Into<String>) -> Self {
+        ParserError::Decompression {
+            context: ErrorContext::default(),
+            message: message.into(),
+        }
+    }
+
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            ParserError::Truncated { context }
+            | ParserError::Decompression { context, .. }
+            | ParserError::MessageDecode { context, .. }
+            | ParserError::InvalidData { context, .. }
+            | ParserError::UnknownClassId { context, .. }
+            | ParserError::UnknownSerializer { context, .. }
+            | ParserError::UnsupportedEncoder { context, .. }
+            | ParserError::FieldDecode { context, .. }
+            | ParserError::InvalidBindings { context, .. } => Some(context),
+            ParserError::Io(_) | ParserError::Listener(_) | ParserError::InvalidInput(_) => None,
+        }
+    }
+
+    fn context_mut(&mut self) -> Option<&mut ErrorContext> {
+        match self {
+            ParserError::Truncated { context }
+            | ParserError::Decompression { context, .. }
+            | ParserError::MessageDecode { context, .. }
+            | ParserError::InvalidData { context, .. }
+            | ParserError::UnknownClassId { context, .. }
+            | ParserError::UnknownSerializer { context, .. }
+            | ParserError::UnsupportedEncoder { context, .. }
+            | ParserError::FieldDecode { context, .. }
+            | ParserError::InvalidBindings { context, .. } => Some(context),
+            ParserError::Io(_) | ParserError::Listener(_) | ParserError::InvalidInput(_) => None,
+        }
+    }
+
+    /// attaches the frame to the error unless a frame is already attached
+    pub(crate) fn with_frame(mut self, tick: u32, command: i32) -> Self {
+        if let Some(context) = self.context_mut()
+            && context.command.is_none()
+        {
+            context.tick = Some(tick);
+            context.command = Some(command);
+        }
+
+        self
+    }
+
+    /// attaches the net message type to the error unless one is already attached
+    pub(crate) fn with_message_type(mut self, message_type: u32) -> Self {
+        if let Some(context) = self.context_mut()
+            && context.message_type.is_none()
+        {
+            context.message_type = Some(message_type);
+        }
+
+        self
+    }
+}
+
+impl fmt::Display for ParserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParserError::Io(err) => write!(f, "I/O error: {err}"),
+            ParserError::Truncated { context } => write!(f, "Unexpected end of demo{context}"),
+            ParserError::Decompression { context, message } => {
+                write!(f, "Failed to decompress{context}: {message}")
+            }
+            ParserError::MessageDecode { context, source } => {
+                write!(f, "Failed to decode message{context}: {source}")
+            }
+            ParserError::InvalidData { context, message } => {
+                write!(f, "Invalid data{context}: {message}")
+            }
+            ParserError::UnknownClassId { context, class_id } => {
+                write!(f, "Unknown class id {class_id}{context}")
+            }
+            ParserError::UnknownSerializer {
+                context,
+                class_name,
+            } => write!(f, "Unknown serializer {class_name}{context}"),
+            ParserError::UnsupportedEncoder {
+                context,
+                var_type,
+                encoder,
+                source,
+            } => write!(
+                f,
+                "Unsupported field type {var_type} (encoder: {encoder:?}){context}: {source}"
+            ),
+            ParserError::FieldDecode {
+                context,
+                path,
+                source,
+            } => write!(f, "Failed to decode field path {path:?}{context}: {source}"),
+            ParserError::InvalidBindings { context, errors } => {
+                write!(f, "Invalid entity bindings{context}:")?;
+                for (class_name, err) in errors {
+                    write!(f, " {class_name}: {err};")?;
+                }
+
+                Ok(())
+            }
+            ParserError::Listener(err) => write!(f, "Listener failed: {err}"),
+            ParserError::InvalidInput(message) => write!(f, "Invalid input: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ParserError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParserError::Io(err) | ParserError::Listener(err) => Some(err),
+            ParserError::MessageDecode { source, .. } => Some(source),
+            ParserError::FieldDecode { source, .. }
+            | ParserError::UnsupportedEncoder { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// an unexpected end inside a frame that was read completely means the data is malformed,
+/// the frame reader maps the end of the demo to `ParserError::Truncated` instead
+impl From<std::io::Error> for ParserError {
+    fn from(err: std::io::Error) -> Self {
+        if err.kind() == std::io::ErrorKind::UnexpectedEof {
+            ParserError::invalid_data(format!("Unexpected end of frame data: {err}"))
+        } else {
+            ParserError::Io(err)
+        }
+    }
+}
+
+impl From<prost::DecodeError> for ParserError {
+    fn from(source: prost::DecodeError) -> Self {
+        ParserError::MessageDecode {
+            context: ErrorContext::default(),
+            source,
+        }
+    }
+}
+
+/// allows returning parser errors from listeners
+impl From<ParserError> for std::io::Error {
+    fn from(err: ParserError) -> Self {
+        match err {
+            ParserError::Io(err) | ParserError::Listener(err) => err,
+            ParserError::Truncated { .. } => {
+                std::io::Error::new(std::io::ErrorKind::UnexpectedEof, err)
+            }
+            ParserError::InvalidInput(_) => {
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, err)
+            }
+            _ => std::io::Error::new(std::io::ErrorKind::InvalidData, err),
+        }
+    }
+}
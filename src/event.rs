@@ -1,9 +1,17 @@
-use std::any::{Any, TypeId};
+use std::{
+    any::{Any, TypeId},
+    collections::VecDeque,
+    sync::Arc,
+};
 
 use foldhash::{HashMap, HashMapExt};
 use log::error;
 
-use crate::CsDemoParserState;
+use crate::{
+    CsDemoParser, CsDemoParserState,
+    entity::{fieldpath::FieldPathFixed, schema::ClassSchema, serializer::EntityField},
+    error::ParserError,
+};
 
 /// notifies listeners before changing the tick
 /// last tick is not notified
@@ -12,7 +20,11 @@ pub struct TickEvent {
     pub tick_interval: f32,
 }
 
-impl Event for TickEvent {}
+impl Event for TickEvent {
+    fn into_demo_event(self) -> Option<DemoEvent> {
+        Some(DemoEvent::Tick(self))
+    }
+}
 
 /// notifies whenever demo parsed the first frame
 pub struct DemoStartEvent {
@@ -20,12 +32,124 @@ pub struct DemoStartEvent {
     pub map_name: String,
 }
 
-impl Event for DemoStartEvent {}
+impl Event for DemoStartEvent {
+    fn into_demo_event(self) -> Option<DemoEvent> {
+        Some(DemoEvent::DemoStart(self))
+    }
+}
 
 /// notifies after the parser reaches the last frame
 pub struct DemoEndEvent;
 
-impl Event for DemoEndEvent {}
+impl Event for DemoEndEvent {
+    fn into_demo_event(self) -> Option<DemoEvent> {
+        Some(DemoEvent::DemoEnd(self))
+    }
+}
+
+/// looks up the entity in the entity list,
+/// None if the slot has been reused or the type doesn't match
+fn get_entity<T: EntityField>(state: &CsDemoParserState, index: u32, serial: u32) -> Option<&T> {
+    state
+        .entities
+        .get(index as usize)
+        .filter(|e| e.serial == serial)
+        .and_then(|e| e.item.downcast_ref::<T>())
+}
+
+/// notifies after an entity has been created and its initial fields are decoded
+pub struct EntityCreatedEvent {
+    pub index: u32,
+    pub serial: u32,
+    pub handle: u64,
+    pub class_name: Arc<str>,
+}
+
+impl EntityCreatedEvent {
+    /// downcasts the entity to the registered type
+    pub fn entity<'a, T: EntityField>(&self, state: &'a CsDemoParserState) -> Option<&'a T> {
+        get_entity(state, self.index, self.serial)
+    }
+}
+
+impl Event for EntityCreatedEvent {
+    fn into_demo_event(self) -> Option<DemoEvent> {
+        Some(DemoEvent::EntityCreated(self))
+    }
+}
+
+/// notifies once per packet after all fields of an existing entity are decoded
+pub struct EntityUpdatedEvent {
+    pub index: u32,
+    pub serial: u32,
+    pub handle: u64,
+    pub class_name: Arc<str>,
+    /// paths of all fields in the update, including fields which are not registered
+    pub changed_fields: Vec<FieldPathFixed>,
+    pub schema: Arc<ClassSchema>,
+    /// the entity before the update,
+    /// only present if enabled with `CsDemoParser::set_track_previous_entity_values`
+    pub previous: Option<Box<dyn Any + Send + Sync>>,
+}
+
+impl EntityUpdatedEvent {
+    /// downcasts the entity to the registered type
+    pub fn entity<'a, T: EntityField>(&self, state: &'a CsDemoParserState) -> Option<&'a T> {
+        get_entity(state, self.index, self.serial)
+    }
+
+    /// downcasts the entity before the update to the registered type
+    pub fn previous<T: EntityField>(&self) -> Option<&T> {
+        self.previous.as_ref()?.downcast_ref::<T>()
+    }
+
+    /// dotted names of the changed fields, see `ClassSchema::resolve_path`
+    pub fn changed_field_names(&self) -> impl Iterator<Item = String> {
+        self.changed_fields
+            .iter()
+            .filter_map(|path| self.schema.resolve_path(path.to_slice()))
+    }
+
+    /// checks if the field or any field nested in it changed,
+    /// e.g. `CBodyComponent` matches a change of `CBodyComponent.m_cellX`
+    pub fn is_changed(&self, name: &str) -> bool {
+        self.changed_field_names().any(|changed| {
+            changed == name
+                || changed
+                    .strip_prefix(name)
+                    .is_some_and(|rest| rest.starts_with('.'))
+        })
+    }
+}
+
+impl Event for EntityUpdatedEvent {
+    fn into_demo_event(self) -> Option<DemoEvent> {
+        Some(DemoEvent::EntityUpdated(self))
+    }
+}
+
+/// notifies after an entity has been removed from the entity list,
+/// the event owns the last state of the entity
+pub struct EntityDeletedEvent {
+    pub index: u32,
+    pub serial: u32,
+    pub handle: u64,
+    pub class_name: Arc<str>,
+    pub item: Box<dyn Any + Send + Sync>,
+}
+
+impl EntityDeletedEvent {
+    /// downcasts the removed entity to the registered type
+    pub fn entity<T: EntityField>(&self) -> Option<&T> {
+        self.item.downcast_ref::<T>()
+    }
+}
+
+impl Event for EntityDeletedEvent {
+    fn into_demo_event(self) -> Option<DemoEvent> {
+        Some(DemoEvent::EntityDeleted(self))
+    }
+}
 
 #[cfg(feature = "handle_packet")]
 pub struct PacketEvent<T: prost::Message + 'static> {
@@ -35,7 +159,104 @@ pub struct PacketEvent<T: prost::Message + 'static> {
 #[cfg(feature = "handle_packet")]
 impl<T: prost::Message + 'static> Event for PacketEvent<T> {}
 
-pub trait Event: Any + Send + Sync {}
+pub trait Event: Any + Send + Sync {
+    /// converts the event for the pull-based API, see `CsDemoParser::events`
+    /// events returning None are only delivered to listeners
+    fn into_demo_event(self) -> Option<DemoEvent>
+    where
+        Self: Sized,
+    {
+        None
+    }
+}
+
+/// built-in events yielded by `CsDemoParser::events`
+pub enum DemoEvent {
+    Tick(TickEvent),
+    DemoStart(DemoStartEvent),
+    DemoEnd(DemoEndEvent),
+    /// a registered game event, use `DemoEvent::game_event` to downcast it
+    GameEvent(Box<dyn Any + Send + Sync>),
+    EntityCreated(EntityCreatedEvent),
+    EntityUpdated(EntityUpdatedEvent),
+    EntityDeleted(EntityDeletedEvent),
+    /// an event derived by the game state, use `DemoEvent::game_state_event` to downcast it
+    GameState(Box<dyn Any + Send + Sync>),
+}
+
+impl DemoEvent {
+    pub fn game_event<E: Event>(&self) -> Option<&E> {
+        if let DemoEvent::GameEvent(event) = self {
+            event.downcast_ref::<E>()
+        } else {
+            None
+        }
+    }
+
+    pub fn game_state_event<E: Event>(&self) -> Option<&E> {
+        if let DemoEvent::GameState(event) = self {
+            event.downcast_ref::<E>()
+        } else {
+            None
+        }
+    }
+}
+
+/// iterator over parsed events, frames are read on demand
+///
+/// listeners registered on the `EventManager` are still notified.
+/// the event queue is disabled once the iterator is dropped
+pub struct DemoEvents<'a, T: std::io::BufRead + Send + Sync> {
+    parser: &'a mut CsDemoParser<T>,
+    finished: bool,
+}
+
+impl<'a, T: std::io::BufRead + Send + Sync> DemoEvents<'a, T> {
+    pub(crate) fn new(parser: &'a mut CsDemoParser<T>) -> Self {
+        parser.event_manager.set_event_queue(true);
+
+        Self {
+            parser,
+            finished: false,
+        }
+    }
+
+    /// state of the parser after the last read frame
+    pub fn state(&self) -> &CsDemoParserState {
+        &self.parser.state
+    }
+}
+
+impl<T: std::io::BufRead + Send + Sync> Iterator for DemoEvents<'_, T> {
+    type Item = Result<DemoEvent, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.parser.event_manager.pop_event() {
+                return Some(Ok(event));
+            }
+
+            if self.finished {
+                return None;
+            }
+
+            match self.parser.read_frame() {
+                Ok(true) => {}
+                Ok(false) => self.finished = true,
+                Err(err) => {
+                    self.finished = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+impl<T: std::io::BufRead + Send + Sync> Drop for DemoEvents<'_, T> {
+    fn drop(&mut self) {
+        self.parser.event_manager.set_event_queue(false);
+    }
+}
 
 pub trait EventListener<T: Event>: Send + Sync {
     fn on_event(&mut self, event: &T, state: &CsDemoParserState) -> Result<(), std::io::Error>;
@@ -71,9 +292,9 @@ impl<T: Event> EventDispatcher<T> {
         listener_id
     }
 
-    pub fn dispatch(&mut self, event: T, state: &CsDemoParserState) -> Result<(), std::io::Error> {
+    pub fn dispatch(&mut self, event: &T, state: &CsDemoParserState) -> Result<(), std::io::Error> {
         for (_, listener) in &mut self.listeners {
-            listener.on_event(&event, state)?;
+            listener.on_event(event, state)?;
         }
 
         Ok(())
@@ -90,11 +311,17 @@ impl<T: Event> EventDispatcher<T> {
             error!("Listener with ID {id} not found");
         }
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.listeners.is_empty()
+    }
 }
 
 #[derive(Default)]
 pub struct EventManager {
     event_listeners: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    /// events waiting to be pulled, None if the pull-based API is not in use
+    event_queue: Option<VecDeque<DemoEvent>>,
 }
 
 impl EventManager {
@@ -102,7 +329,35 @@ impl EventManager {
     pub fn new() -> Self {
         Self {
             event_listeners: HashMap::new(),
+            event_queue: None,
+        }
+    }
+
+    /// enables or disables queueing of built-in events,
+    /// disabling drops all events left in the queue
+    pub fn set_event_queue(&mut self, enabled: bool) {
+        if !enabled {
+            self.event_queue = None;
+        } else if self.event_queue.is_none() {
+            self.event_queue = Some(VecDeque::with_capacity(64));
+        }
+    }
+
+    pub fn pop_event(&mut self) -> Option<DemoEvent> {
+        self.event_queue.as_mut()?.pop_front()
+    }
+
+    /// checks if an event would be delivered to any listener or the event queue,
+    /// allows skipping the construction of frequent events
+    pub fn is_subscribed<E: Event>(&self) -> bool {
+        if self.event_queue.is_some() {
+            return true;
         }
+
+        self.event_listeners
+            .get(&TypeId::of::<E>())
+            .and_then(|listeners| listeners.downcast_ref::<EventDispatcher<E>>())
+            .is_some_and(|dispatcher| !dispatcher.is_empty())
     }
 
     pub fn register_listener<L: EventListener<E> + 'static, E: Event>(
@@ -135,13 +390,21 @@ impl EventManager {
         &mut self,
         event: E,
         state: &CsDemoParserState,
-    ) -> Result<(), std::io::Error> {
+    ) -> Result<(), ParserError> {
         let type_id = TypeId::of::<E>();
         if let Some(listeners) = self.event_listeners.get_mut(&type_id) {
             let dispatcher = listeners.downcast_mut::<EventDispatcher<E>>().unwrap();
-            dispatcher.dispatch(event, state)
-        } else {
-            Ok(())
+            dispatcher
+                .dispatch(&event, state)
+                .map_err(ParserError::Listener)?;
         }
+
+        if let Some(queue) = self.event_queue.as_mut()
+            && let Some(event) = event.into_demo_event()
+        {
+            queue.push_back(event);
+        }
+
+        Ok(())
     }
 }
@@ -2,26 +2,26 @@ pub mod derive;
 
 use log::error;
 
-use crate::{CsDemoParser, game_event::derive::GameEventSerializerFactory, protobuf};
+use crate::{
+    CsDemoParser, error::ParserError, game_event::derive::GameEventSerializerFactory, protobuf,
+};
 
 impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
     pub fn register_game_event_serializer_factory(
         &mut self,
         event_name: &'static str,
         factory: GameEventSerializerFactory,
-    ) -> Result<(), std::io::Error> {
+    ) -> Result<(), ParserError> {
         if !self.is_fresh() {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "Cannot register game event serializer after parsing has started",
+            return Err(ParserError::InvalidInput(
+                "Cannot register game event serializer after parsing has started".to_string(),
             ));
         }
 
         if self.game_event_serializers.contains_key(&event_name) {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::AlreadyExists,
-                format!("Game event serializer for '{event_name}' already exists"),
-            ));
+            return Err(ParserError::InvalidInput(format!(
+                "Game event serializer for '{event_name}' already exists"
+            )));
         }
 
         self.game_event_serializers.insert(event_name, factory);
@@ -32,12 +32,14 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
     pub(super) fn handle_legacy_game_event(
         &mut self,
         msg: protobuf::CMsgSource1LegacyGameEvent,
-    ) -> Result<(), std::io::Error> {
+    ) -> Result<(), ParserError> {
         let Some(event_id) = msg.eventid else {
             error!("Missing event ID in legacy game event");
             return Ok(());
         };
 
+        self.handle_game_state_event(event_id, &msg.keys)?;
+
         let Some(serializer) = self.game_event_list.get(&event_id) else {
             return Ok(());
         };
@@ -51,8 +53,9 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
     pub(super) fn handle_legacy_game_event_list(
         &mut self,
         msg: protobuf::CMsgSource1LegacyGameEventList,
-    ) -> Result<(), std::io::Error> {
+    ) -> Result<(), ParserError> {
         self.game_event_list.clear();
+        self.set_game_state_event_list(&msg.descriptors);
 
         for descriptor in msg.descriptors.into_iter() {
             let (Some(event_id), Some(event_name)) = (descriptor.eventid, descriptor.name) else {
@@ -1,4 +1,4 @@
-use crate::{event::EventManager, protobuf};
+use crate::{error::ParserError, event::EventManager, protobuf};
 
 pub use macro_derive::GameEvent;
 
@@ -6,7 +6,7 @@ pub type ListKeysT = [(u32, String)];
 pub type KeyT = protobuf::c_msg_source1_legacy_game_event::KeyT;
 
 pub type GameEventSerializerFactory =
-    fn(keys: &ListKeysT) -> Result<Box<dyn GameEventSerializer>, std::io::Error>;
+    fn(keys: &ListKeysT) -> Result<Box<dyn GameEventSerializer>, ParserError>;
 
 pub trait GameEventSerializer: Send + Sync {
     fn parse_and_dispatch_event(
@@ -14,5 +14,5 @@ pub trait GameEventSerializer: Send + Sync {
         keys: Vec<KeyT>,
         event_manager: &mut EventManager,
         state: &crate::CsDemoParserState,
-    ) -> Result<(), std::io::Error>;
+    ) -> Result<(), ParserError>;
 }
@@ -0,0 +1,662 @@
+/// implements `Event` for events derived by the game state
+macro_rules! game_state_event {
+    ($($name:ident),*) => {
+        $(
+            impl crate::event::Event for $name {
+                fn into_demo_event(self) -> Option<crate::event::DemoEvent> {
+                    Some(crate::event::DemoEvent::GameState(Box::new(self)))
+                }
+            }
+        )*
+    };
+}
+
+pub mod bomb;
+pub mod classes;
+pub mod damage;
+pub mod economy;
+pub mod grenade;
+pub mod inferno;
+pub mod inventory;
+pub mod round;
+
+use std::sync::Arc;
+
+use foldhash::HashMap;
+
+use crate::{
+    CsDemoParser, CsDemoParserState, EntitySerializerCreator,
+    entity::{
+        list::{EntityHandle, EntityItem, EntityList},
+        schema::ClassSchema,
+        serializer::vector::Vector3,
+    },
+    error::ParserError,
+    event::{Event, EventManager},
+    game_event::derive::KeyT,
+    game_state::{
+        bomb::{BOMB_GAME_EVENTS, BombState},
+        classes::{
+            CBaseCSGrenadeProjectile, CC4, CCSGameRulesProxy, CCSPlayerController, CCSPlayerPawn,
+            CCSTeam, CCSWeaponBase, CInferno, CPlantedC4, GamePhase, Team,
+        },
+        damage::{DAMAGE_GAME_EVENTS, DamageLedger},
+        economy::{ECONOMY_GAME_EVENTS, Economy},
+        grenade::{GRENADE_GAME_EVENTS, Grenade, GrenadeTracker},
+        inferno::{Inferno, InfernoTracker},
+        inventory::{Inventory, InventoryTracker},
+        round::{ROUND_GAME_EVENTS, RoundPhase, RoundTracker},
+    },
+    protobuf::c_msg_source1_legacy_game_event_list::DescriptorT,
+};
+
+/// classes decoded by the built-in definitions while the game state is enabled
+pub(crate) const GAME_STATE_CLASSES: [(&str, EntitySerializerCreator); 7] = [
+    ("CCSPlayerController", CCSPlayerController::new_serializer),
+    ("CCSPlayerPawn", CCSPlayerPawn::new_serializer),
+    ("CCSTeam", CCSTeam::new_serializer),
+    ("CCSGameRulesProxy", CCSGameRulesProxy::new_serializer),
+    ("CC4", CC4::new_serializer),
+    ("CPlantedC4", CPlantedC4::new_serializer),
+    ("CInferno", CInferno::new_serializer),
+];
+
+/// decoded as `CBaseCSGrenadeProjectile`, e.g. `CSmokeGrenadeProjectile`
+fn is_projectile_class(class_name: &str) -> bool {
+    class_name.starts_with('C') && class_name.ends_with("Projectile")
+}
+
+/// classes of weapons decoded as `CCSWeaponBase`,
+/// every class with an item definition and a clip as the class names don't follow a pattern
+fn is_weapon_class(schema: &ClassSchema) -> bool {
+    schema.has_field("m_AttributeManager.m_Item.m_iItemDefinitionIndex")
+        && schema.has_field("m_iClip1")
+}
+
+/// the built-in definition a class is decoded with while the game state is enabled
+pub(crate) fn game_state_serializer(schema: &ClassSchema) -> Option<EntitySerializerCreator> {
+    let class_name = schema.name.as_ref();
+
+    if let Some((_, creator)) = GAME_STATE_CLASSES
+        .iter()
+        .find(|(name, _)| *name == class_name)
+    {
+        Some(*creator)
+    } else if is_projectile_class(class_name) {
+        Some(CBaseCSGrenadeProjectile::new_serializer)
+    } else if is_weapon_class(schema) {
+        Some(CCSWeaponBase::new_serializer)
+    } else {
+        None
+    }
+}
+
+/// game events read by the game state regardless of the registered serializers
+const GAME_STATE_EVENTS: [&[&str]; 5] = [
+    &ROUND_GAME_EVENTS,
+    &BOMB_GAME_EVENTS,
+    &GRENADE_GAME_EVENTS,
+    &DAMAGE_GAME_EVENTS,
+    &ECONOMY_GAME_EVENTS,
+];
+
+/// `PlayerConnectedState::PlayerConnected`
+const PLAYER_CONNECTED: i32 = 0;
+
+/// a participant of the match, combines the controller and its pawn
+#[derive(Clone, Debug)]
+pub struct Player {
+    pub controller: EntityHandle<CCSPlayerController>,
+    /// invalid while the player has no pawn, e.g. as spectator
+    pub pawn: EntityHandle<CCSPlayerPawn>,
+    pub name: String,
+    pub steam_id: u64,
+    pub team: Team,
+    pub is_connected: bool,
+    pub is_alive: bool,
+    pub health: u32,
+    pub armor: i32,
+    pub has_helmet: bool,
+    pub has_defuser: bool,
+    pub score: i32,
+    /// world position of the pawn, None if the pawn is not networked
+    pub position: Option<Vector3>,
+    pub money: i32,
+    /// money at the start of the round
+    pub start_money: i32,
+    pub cash_spent_this_round: i32,
+    pub total_cash_spent: i32,
+    /// value of the current equipment, zero if the pawn is not networked
+    pub equipment_value: i32,
+    pub round_start_equipment_value: i32,
+    /// value of the equipment when the freeze time ended, the usual basis of buy classifications
+    pub freeze_time_end_equipment_value: i32,
+}
+
+impl Player {
+    fn new(item: &EntityItem) -> Self {
+        Self {
+            controller: EntityHandle::from_raw(item.get_handle()),
+            pawn: EntityHandle::INVALID,
+            name: String::new(),
+            steam_id: 0,
+            team: Team::Unassigned,
+            is_connected: false,
+            is_alive: false,
+            health: 0,
+            armor: 0,
+            has_helmet: false,
+            has_defuser: false,
+            score: 0,
+            position: None,
+            money: 0,
+            start_money: 0,
+            cash_spent_this_round: 0,
+            total_cash_spent: 0,
+            equipment_value: 0,
+            round_start_equipment_value: 0,
+            freeze_time_end_equipment_value: 0,
+        }
+    }
+
+    fn update(&mut self, controller: &CCSPlayerController, pawn: Option<&CCSPlayerPawn>) {
+        if self.name != controller.player_name {
+            self.name.clone_from(&controller.player_name);
+        }
+
+        self.pawn = controller.player_pawn;
+        self.steam_id = controller.steam_id;
+        self.team = controller.team;
+        self.is_connected = controller.connected == PLAYER_CONNECTED;
+        self.is_alive = controller.pawn_is_alive;
+        self.health = controller.pawn_health;
+        self.armor = controller.pawn_armor;
+        self.has_helmet = controller.pawn_has_helmet;
+        self.has_defuser = controller.pawn_has_defuser;
+        self.score = controller.score;
+        self.position = pawn.map(|pawn| pawn.position.world());
+        self.money = controller.account;
+        self.start_money = controller.start_account;
+        self.cash_spent_this_round = controller.cash_spent_this_round;
+        self.total_cash_spent = controller.total_cash_spent;
+        self.equipment_value = pawn.map_or(0, |pawn| pawn.current_equipment_value as i32);
+        self.round_start_equipment_value =
+            pawn.map_or(0, |pawn| pawn.round_start_equipment_value as i32);
+        self.freeze_time_end_equipment_value =
+            pawn.map_or(0, |pawn| pawn.freezetime_end_equipment_value as i32);
+    }
+
+    /// player slot used by game events and `CsDemoParserState::get_player_info`
+    pub fn slot(&self) -> u16 {
+        (self.controller.index() - 1) as u16
+    }
+
+    /// true for connected players on the terrorist or counter-terrorist side
+    pub fn is_playing(&self) -> bool {
+        self.is_connected && self.team.is_playing()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TeamState {
+    pub team: Team,
+    pub name: String,
+    pub clan_name: String,
+    pub score: i32,
+    pub score_first_half: i32,
+    pub score_second_half: i32,
+    pub score_overtime: i32,
+    pub surrendered: bool,
+}
+
+impl TeamState {
+    fn update(&mut self, team: &CCSTeam) {
+        if self.name != team.team_name {
+            self.name.clone_from(&team.team_name);
+        }
+        if self.clan_name != team.clan_name {
+            self.clan_name.clone_from(&team.clan_name);
+        }
+
+        self.team = team.team;
+        self.score = team.score;
+        self.score_first_half = team.score_first_half;
+        self.score_second_half = team.score_second_half;
+        self.score_overtime = team.score_overtime;
+        self.surrendered = team.surrendered;
+    }
+}
+
+type PendingEvent =
+    Box<dyn FnOnce(&mut EventManager, &CsDemoParserState) -> Result<(), ParserError> + Send + Sync>;
+
+/// events derived while updating the game state,
+/// dispatched once the update is done and the state is consistent again
+#[derive(Default)]
+pub(crate) struct PendingEvents(Vec<PendingEvent>);
+
+impl PendingEvents {
+    pub(crate) fn push<E: Event>(&mut self, event: E) {
+        self.0.push(Box::new(move |event_manager, state| {
+            event_manager.notify_listeners(event, state)
+        }));
+    }
+}
+
+/// keys of a game event looked up by name
+pub(crate) struct GameEventKeys<'a> {
+    names: &'a [String],
+    keys: &'a [KeyT],
+}
+
+impl GameEventKeys<'_> {
+    fn get(&self, name: &str) -> Option<&KeyT> {
+        let idx = self.names.iter().position(|n| n == name)?;
+        self.keys.get(idx)
+    }
+
+    /// integer keys of any width
+    pub(crate) fn int(&self, name: &str) -> Option<i64> {
+        let key = self.get(name)?;
+
+        key.val_long
+            .or(key.val_short)
+            .or(key.val_byte)
+            .map(i64::from)
+            .or(key.val_uint64.map(|v| v as i64))
+    }
+
+    pub(crate) fn bool(&self, name: &str) -> Option<bool> {
+        self.get(name)?.val_bool
+    }
+
+    pub(crate) fn float(&self, name: &str) -> Option<f32> {
+        self.get(name)?.val_float
+    }
+
+    pub(crate) fn string(&self, name: &str) -> Option<&str> {
+        self.get(name)?.val_string.as_deref()
+    }
+}
+
+/// players, teams and rules of the match,
+/// refreshed from the built-in entity classes after every entity update
+///
+/// enable it with `CsDemoParser::enable_game_state`
+/// and read it with `CsDemoParserState::game_state`
+#[derive(Default)]
+pub struct GameState {
+    /// ordered by the entity index of the controller
+    players: Vec<Player>,
+    teams: Vec<TeamState>,
+    rules: CCSGameRulesProxy,
+    round: RoundTracker,
+    bomb: BombState,
+    grenades: GrenadeTracker,
+    infernos: InfernoTracker,
+    damage: DamageLedger,
+    economy: Economy,
+    inventories: InventoryTracker,
+
+    /// name and key names of the game events read by the game state, by event id
+    game_events: HashMap<i32, (&'static str, Arc<[String]>)>,
+    pending_events: PendingEvents,
+}
+
+impl GameState {
+    /// every controller including spectators, bots and disconnected players
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    /// connected players on the terrorist or counter-terrorist side
+    pub fn playing(&self) -> impl Iterator<Item = &Player> {
+        self.players.iter().filter(|p| p.is_playing())
+    }
+
+    /// connected players of the team
+    pub fn team_members(&self, team: Team) -> impl Iterator<Item = &Player> {
+        self.players
+            .iter()
+            .filter(move |p| p.is_connected && p.team == team)
+    }
+
+    /// looks up a player by the slot of game events
+    pub fn player_by_slot(&self, slot: u16) -> Option<&Player> {
+        self.player_by_index(slot as u32 + 1)
+    }
+
+    /// looks up a player by the entity index of the controller
+    pub fn player_by_index(&self, index: u32) -> Option<&Player> {
+        self.players
+            .binary_search_by_key(&index, |p| p.controller.index())
+            .ok()
+            .map(|i| &self.players[i])
+    }
+
+    /// looks up the owner of a pawn
+    pub fn player_by_pawn(&self, pawn: EntityHandle<CCSPlayerPawn>) -> Option<&Player> {
+        if !pawn.is_valid() {
+            return None;
+        }
+
+        self.players.iter().find(|p| p.pawn == pawn)
+    }
+
+    pub fn player_by_steam_id(&self, steam_id: u64) -> Option<&Player> {
+        self.players.iter().find(|p| p.steam_id == steam_id)
+    }
+
+    pub fn team(&self, team: Team) -> Option<&TeamState> {
+        self.teams.iter().find(|t| t.team == team)
+    }
+
+    pub fn teams(&self) -> &[TeamState] {
+        &self.teams
+    }
+
+    /// score of the team, zero if the team entity doesn't exist yet
+    pub fn score(&self, team: Team) -> i32 {
+        self.team(team).map_or(0, |t| t.score)
+    }
+
+    /// latest networked values of `CCSGameRules`
+    pub fn rules(&self) -> &CCSGameRulesProxy {
+        &self.rules
+    }
+
+    /// number of rounds played, i.e. the zero based number of the current round
+    pub fn total_rounds_played(&self) -> i32 {
+        self.rules.total_rounds_played
+    }
+
+    pub fn game_phase(&self) -> GamePhase {
+        self.rules.game_phase
+    }
+
+    pub fn is_warmup(&self) -> bool {
+        self.rules.warmup_period
+    }
+
+    pub fn is_freeze_period(&self) -> bool {
+        self.rules.freeze_period
+    }
+
+    pub fn has_match_started(&self) -> bool {
+        self.rules.has_match_started
+    }
+
+    /// one based number of the current round, see `RoundStartEvent`
+    pub fn round(&self) -> i32 {
+        self.round.round
+    }
+
+    pub fn round_phase(&self) -> RoundPhase {
+        self.round.phase
+    }
+
+    /// the bomb of the current round
+    pub fn bomb(&self) -> &BombState {
+        &self.bomb
+    }
+
+    /// grenades in flight, smokes until they fade
+    pub fn grenades(&self) -> &[Grenade] {
+        &self.grenades.active
+    }
+
+    /// fires of molotovs and incendiaries until the entity is removed
+    pub fn infernos(&self) -> &[Inferno] {
+        &self.infernos.active
+    }
+
+    /// damage of every `player_hurt` outside of warmup
+    pub fn damage(&self) -> &DamageLedger {
+        &self.damage
+    }
+
+    /// average damage per round of the player to opponents over the rounds played so far
+    pub fn adr(&self, controller: EntityHandle<CCSPlayerController>) -> f32 {
+        self.damage
+            .average_per_round(controller, self.rules.total_rounds_played)
+    }
+
+    /// balances, purchases and buy types of every round outside of warmup
+    pub fn economy(&self) -> &Economy {
+        &self.economy
+    }
+
+    /// the weapons of every player, ordered like the players
+    pub fn inventories(&self) -> &[Inventory] {
+        &self.inventories.inventories
+    }
+
+    pub fn inventory(&self, controller: EntityHandle<CCSPlayerController>) -> Option<&Inventory> {
+        self.inventories
+            .inventories
+            .iter()
+            .find(|i| i.player == controller)
+    }
+
+    /// looks up a player by the handle of the controller
+    pub fn player(&self, controller: EntityHandle<CCSPlayerController>) -> Option<&Player> {
+        self.player_by_index(controller.index())
+            .filter(|p| p.controller == controller)
+    }
+
+    /// refreshes the snapshots from the entity list, allocations are reused
+    fn update(&mut self, entities: &EntityList, tick: u32) {
+        let mut len = 0;
+        for (item, controller) in entities.iter_entity::<CCSPlayerController>() {
+            let pawn = controller.player_pawn.resolve(entities);
+            let handle = EntityHandle::from_raw(item.get_handle());
+
+            match self.players.get_mut(len) {
+                Some(player) if player.controller == handle => {}
+                Some(player) => *player = Player::new(item),
+                None => self.players.push(Player::new(item)),
+            }
+
+            self.players[len].update(controller, pawn);
+            len += 1;
+        }
+        self.players.truncate(len);
+
+        let mut len = 0;
+        for (_, team) in entities.iter_entity::<CCSTeam>() {
+            if len == self.teams.len() {
+                self.teams.push(TeamState::default());
+            }
+
+            self.teams[len].update(team);
+            len += 1;
+        }
+        self.teams.truncate(len);
+
+        if let Some((_, rules)) = entities.iter_entity::<CCSGameRulesProxy>().next() {
+            let previous = std::mem::replace(&mut self.rules, rules.clone());
+            self.round
+                .on_rules_changed(&previous, &self.rules, &mut self.pending_events);
+        }
+
+        self.reset_round_trackers();
+        self.bomb
+            .on_entities_updated(entities, &self.players, tick, &mut self.pending_events);
+        self.grenades
+            .on_entities_updated(entities, &self.players, tick, &mut self.pending_events);
+        self.infernos
+            .on_entities_updated(entities, &self.players, tick, &mut self.pending_events);
+        self.inventories
+            .on_entities_updated(entities, &self.players, &mut self.pending_events);
+        self.economy.on_entities_updated(
+            &self.players,
+            &self.rules,
+            &self.round,
+            &self.inventories.pickups,
+            tick,
+            &mut self.pending_events,
+        );
+    }
+
+    /// the trackers of the previous round are cleared once a new round starts
+    fn reset_round_trackers(&mut self) {
+        if std::mem::take(&mut self.round.restarted) {
+            self.bomb.reset(self.round.round);
+            self.damage.restart(self.round.round);
+            self.economy.restart();
+        }
+        if self.bomb.round != self.round.round {
+            self.bomb.reset(self.round.round);
+        }
+        if self.damage.round != self.round.round {
+            self.damage.reset(self.round.round);
+        }
+    }
+
+    fn set_game_event_list(&mut self, descriptors: &[DescriptorT]) {
+        self.game_events.clear();
+
+        for descriptor in descriptors {
+            let (Some(event_id), Some(event_name)) = (descriptor.eventid, &descriptor.name) else {
+                continue;
+            };
+
+            let Some(name) = GAME_STATE_EVENTS
+                .iter()
+                .flat_map(|names| names.iter())
+                .find(|name| **name == event_name)
+            else {
+                continue;
+            };
+
+            let keys = descriptor
+                .keys
+                .iter()
+                .map(|key| key.name.clone().unwrap_or_default())
+                .collect();
+            self.game_events.insert(event_id, (name, keys));
+        }
+    }
+
+    fn handle_game_event(&mut self, event_id: i32, keys: &[KeyT], tick: u32) {
+        let Some((name, names)) = self.game_events.get(&event_id).cloned() else {
+            return;
+        };
+
+        let keys = GameEventKeys {
+            names: &names,
+            keys,
+        };
+        self.round
+            .on_game_event(name, &keys, &self.rules, &mut self.pending_events);
+        self.reset_round_trackers();
+        self.bomb
+            .on_game_event(name, &keys, &self.players, tick, &mut self.pending_events);
+        self.grenades
+            .on_game_event(name, &keys, &self.players, tick, &mut self.pending_events);
+        self.damage.on_game_event(
+            name,
+            &keys,
+            &self.players,
+            &self.rules,
+            tick,
+            &mut self.pending_events,
+        );
+        self.economy
+            .on_game_event(name, &keys, &self.players, tick, &mut self.pending_events);
+    }
+
+    /// drops all snapshots and trackers, e.g. after the entity list has been rebuilt by seeking
+    pub(crate) fn reset(&mut self) {
+        *self = Self {
+            game_events: std::mem::take(&mut self.game_events),
+            ..Self::default()
+        };
+    }
+}
+
+impl CsDemoParserState {
+    /// None unless enabled with `CsDemoParser::enable_game_state`
+    pub fn game_state(&self) -> Option<&GameState> {
+        self.game_state.as_ref()
+    }
+}
+
+impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
+    /// decodes `CCSPlayerController`, `CCSPlayerPawn`, `CCSTeam`, `CCSGameRulesProxy`,
+    /// `CC4`, `CPlantedC4`, `CInferno`, every `C*Projectile` and every weapon
+    /// with the built-in classes of `game_state::classes` and maintains a `GameState`
+    ///
+    /// a serializer registered by name or by a matcher takes precedence over the built-in class,
+    /// the game state then skips the entities of that class, e.g. the inventories miss
+    /// the weapons decoded by a `Weapon` struct of the user, and a warning is logged
+    pub fn enable_game_state(&mut self) -> Result<(), ParserError> {
+        if !self.is_fresh() {
+            return Err(ParserError::InvalidInput(
+                "Cannot enable game state after parsing has started".to_string(),
+            ));
+        }
+
+        self.state.game_state = Some(GameState::default());
+
+        Ok(())
+    }
+
+    pub(super) fn update_game_state(&mut self) -> Result<(), ParserError> {
+        let tick = self.state.tick;
+        let CsDemoParserState {
+            game_state: Some(game_state),
+            entities,
+            ..
+        } = &mut self.state
+        else {
+            return Ok(());
+        };
+
+        game_state.update(entities, tick);
+
+        self.dispatch_game_state_events()
+    }
+
+    pub(super) fn set_game_state_event_list(&mut self, descriptors: &[DescriptorT]) {
+        if let Some(game_state) = self.state.game_state.as_mut() {
+            game_state.set_game_event_list(descriptors);
+        }
+    }
+
+    pub(super) fn handle_game_state_event(
+        &mut self,
+        event_id: i32,
+        keys: &[KeyT],
+    ) -> Result<(), ParserError> {
+        let Some(game_state) = self.state.game_state.as_mut() else {
+            return Ok(());
+        };
+
+        game_state.handle_game_event(event_id, keys, self.state.tick);
+
+        self.dispatch_game_state_events()
+    }
+
+    fn dispatch_game_state_events(&mut self) -> Result<(), ParserError> {
+        let Some(game_state) = self.state.game_state.as_mut() else {
+            return Ok(());
+        };
+
+        if game_state.pending_events.0.is_empty() {
+            return Ok(());
+        }
+
+        let mut events = std::mem::take(&mut game_state.pending_events.0);
+        let result = events
+            .drain(..)
+            .try_for_each(|notify| notify(&mut self.event_manager, &self.state));
+
+        // keep the allocation for the next update
+        if let Some(game_state) = self.state.game_state.as_mut() {
+            game_state.pending_events.0 = events;
+        }
+
+        result
+    }
+}
@@ -0,0 +1,481 @@
+use crate::{
+    entity::{
+        list::{EntityHandle, EntityList},
+        serializer::vector::Vector3,
+    },
+    game_state::{
+        GameEventKeys, PendingEvents, Player,
+        classes::{CC4, CCSPlayerController, CPlantedC4},
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BombSite {
+    A,
+    B,
+}
+
+impl BombSite {
+    /// `m_nBombSite` of `CPlantedC4`
+    fn from_index(index: i32) -> Option<Self> {
+        match index {
+            0 => Some(BombSite::A),
+            1 => Some(BombSite::B),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum BombPhase {
+    /// no bomb in play, e.g. outside of defuse mode
+    #[default]
+    None,
+    Carried,
+    Dropped,
+    Planting,
+    Planted,
+    Defusing,
+    Defused,
+    Exploded,
+}
+
+/// notifies when a player picks up the bomb, including the bomb handed out at round start
+pub struct BombPickupEvent {
+    pub player: EntityHandle<CCSPlayerController>,
+}
+
+pub struct BombDropEvent {
+    pub player: EntityHandle<CCSPlayerController>,
+    pub position: Option<Vector3>,
+}
+
+pub struct BombPlantBeginEvent {
+    pub player: EntityHandle<CCSPlayerController>,
+    /// None until the site has been seen in a completed plant
+    pub site: Option<BombSite>,
+}
+
+pub struct BombPlantAbortEvent {
+    pub player: EntityHandle<CCSPlayerController>,
+    pub site: Option<BombSite>,
+}
+
+pub struct BombPlantedEvent {
+    /// invalid if the planter has not been observed
+    pub player: EntityHandle<CCSPlayerController>,
+    pub site: Option<BombSite>,
+    pub position: Vector3,
+    /// seconds until the explosion
+    pub timer_length: f32,
+}
+
+pub struct BombDefuseBeginEvent {
+    pub player: EntityHandle<CCSPlayerController>,
+    pub has_kit: bool,
+}
+
+pub struct BombDefuseAbortEvent {
+    pub player: EntityHandle<CCSPlayerController>,
+    pub has_kit: bool,
+}
+
+pub struct BombDefusedEvent {
+    pub player: EntityHandle<CCSPlayerController>,
+    pub site: Option<BombSite>,
+}
+
+pub struct BombExplodedEvent {
+    pub site: Option<BombSite>,
+}
+
+game_state_event!(
+    BombPickupEvent,
+    BombDropEvent,
+    BombPlantBeginEvent,
+    BombPlantAbortEvent,
+    BombPlantedEvent,
+    BombDefuseBeginEvent,
+    BombDefuseAbortEvent,
+    BombDefusedEvent,
+    BombExplodedEvent
+);
+
+/// game events which drive the bomb lifecycle
+pub(crate) const BOMB_GAME_EVENTS: [&str; 9] = [
+    "bomb_pickup",
+    "bomb_dropped",
+    "bomb_beginplant",
+    "bomb_abortplant",
+    "bomb_planted",
+    "bomb_begindefuse",
+    "bomb_abortdefuse",
+    "bomb_defused",
+    "bomb_exploded",
+];
+
+/// the bomb of the current round,
+/// derived from the `bomb_*` game events and the `CC4` and `CPlantedC4` entities
+///
+/// like the round events, every transition is notified once
+/// no matter whether the game event or the entity update arrives first
+#[derive(Clone, Debug, Default)]
+pub struct BombState {
+    pub phase: BombPhase,
+    pub carrier: Option<EntityHandle<CCSPlayerController>>,
+    /// the player planting or defusing the bomb, or the one who did
+    pub planter: Option<EntityHandle<CCSPlayerController>>,
+    pub defuser: Option<EntityHandle<CCSPlayerController>>,
+    pub defuser_has_kit: bool,
+    pub site: Option<BombSite>,
+    /// position of the dropped or planted bomb
+    pub position: Option<Vector3>,
+    pub planted_tick: Option<u32>,
+    /// seconds from the plant until the explosion
+    pub timer_length: f32,
+    pub defuse_start_tick: Option<u32>,
+    /// seconds the current defuse takes, depends on the kit
+    pub defuse_length: f32,
+
+    /// the round the state belongs to, the state is cleared once a new round starts
+    pub(crate) round: i32,
+    /// entity indices of the bomb site triggers of the game events, learned from completed plants
+    site_triggers: Vec<(i64, BombSite)>,
+    /// site trigger of the last plant game event, paired with the site of the planted bomb
+    plant_trigger: Option<i64>,
+    /// last seen `m_bStartedArming` of the carried bomb
+    started_arming: bool,
+    /// last seen `m_bBeingDefused` of the planted bomb
+    being_defused: bool,
+}
+
+impl BombState {
+    /// seconds until the explosion at the given tick, None unless the bomb is ticking
+    pub fn time_left(&self, tick: u32, tick_interval: f32) -> Option<f32> {
+        if !matches!(self.phase, BombPhase::Planted | BombPhase::Defusing) {
+            return None;
+        }
+
+        let elapsed = tick.saturating_sub(self.planted_tick?) as f32 * tick_interval;
+        Some((self.timer_length - elapsed).max(0.0))
+    }
+
+    /// seconds until the running defuse completes at the given tick
+    pub fn defuse_time_left(&self, tick: u32, tick_interval: f32) -> Option<f32> {
+        if self.phase != BombPhase::Defusing {
+            return None;
+        }
+
+        let elapsed = tick.saturating_sub(self.defuse_start_tick?) as f32 * tick_interval;
+        Some((self.defuse_length - elapsed).max(0.0))
+    }
+
+    /// starts over for a new round, the learned bomb sites are kept
+    pub(crate) fn reset(&mut self, round: i32) {
+        *self = Self {
+            round,
+            site_triggers: std::mem::take(&mut self.site_triggers),
+            ..Self::default()
+        };
+    }
+
+    pub(crate) fn on_entities_updated(
+        &mut self,
+        entities: &EntityList,
+        players: &[Player],
+        tick: u32,
+        events: &mut PendingEvents,
+    ) {
+        let controller_of = |pawn| {
+            players
+                .iter()
+                .find(|p| p.pawn.is_valid() && p.pawn.raw() == pawn)
+                .map(|p| p.controller)
+        };
+
+        // a bomb left over from the previous round is no longer ticking,
+        // the bomb of this round is still carried then
+        if let Some((_, planted)) = entities.iter_entity::<CPlantedC4>().next()
+            && (planted.bomb_ticking
+                || matches!(self.phase, BombPhase::Planted | BombPhase::Defusing))
+        {
+            self.site = BombSite::from_index(planted.bomb_site).or(self.site);
+            self.learn_site();
+            self.position = Some(planted.position.world());
+            self.timer_length = planted.timer_length;
+            self.defuse_length = planted.defuse_length;
+
+            if planted.bomb_ticking {
+                self.planted(self.planter, tick, events);
+            }
+
+            let defuser = controller_of(planted.bomb_defuser.raw());
+            let was_being_defused =
+                std::mem::replace(&mut self.being_defused, planted.being_defused);
+            if planted.has_exploded {
+                self.exploded(events);
+            } else if planted.bomb_defused {
+                self.defused(defuser.or(self.defuser), events);
+            } else if planted.being_defused {
+                if let Some(defuser) = defuser {
+                    let has_kit = players
+                        .iter()
+                        .find(|p| p.controller == defuser)
+                        .is_some_and(|p| p.has_defuser);
+                    self.defuse_begin(defuser, has_kit, tick, events);
+                }
+            } else if was_being_defused {
+                // `bomb_begindefuse` may arrive before the defuse is networked
+                self.defuse_abort(events);
+            }
+
+            return;
+        }
+
+        let Some((_, c4)) = entities.iter_entity::<CC4>().next() else {
+            return;
+        };
+
+        match controller_of(c4.weapon.owner.raw()) {
+            Some(carrier) => {
+                self.pickup(carrier, events);
+
+                if c4.started_arming && !self.started_arming {
+                    self.plant_begin(carrier, None, events);
+                } else if !c4.started_arming && self.started_arming {
+                    self.plant_abort(None, events);
+                }
+                self.started_arming = c4.started_arming;
+            }
+            None => {
+                self.started_arming = false;
+                self.drop(Some(c4.position.world()), events);
+                if self.phase == BombPhase::Dropped {
+                    self.position = Some(c4.position.world());
+                }
+            }
+        }
+    }
+
+    pub(crate) fn on_game_event(
+        &mut self,
+        name: &str,
+        keys: &GameEventKeys<'_>,
+        players: &[Player],
+        tick: u32,
+        events: &mut PendingEvents,
+    ) {
+        let player = keys.int("userid").and_then(|slot| {
+            players
+                .iter()
+                .find(|p| p.slot() as i64 == slot)
+                .map(|p| p.controller)
+        });
+        let trigger = keys.int("site");
+
+        match name {
+            "bomb_pickup" => {
+                if let Some(player) = player {
+                    self.pickup(player, events);
+                }
+            }
+            "bomb_dropped" => {
+                let position = players
+                    .iter()
+                    .find(|p| Some(p.controller) == player)
+                    .and_then(|p| p.position.clone());
+                self.drop(position, events);
+            }
+            "bomb_beginplant" => {
+                if let Some(player) = player {
+                    self.plant_begin(player, trigger, events);
+                }
+            }
+            "bomb_abortplant" => self.plant_abort(trigger, events),
+            "bomb_planted" => {
+                // the planted bomb is notified with the `CPlantedC4` entity which carries the site
+                if player.is_some() && self.phase != BombPhase::Planted {
+                    self.planter = player;
+                }
+                self.plant_trigger = trigger;
+                self.learn_site();
+            }
+            "bomb_begindefuse" => {
+                if let Some(player) = player {
+                    let has_kit = keys.bool("haskit").unwrap_or_default();
+                    self.defuse_begin(player, has_kit, tick, events);
+                }
+            }
+            "bomb_abortdefuse" => self.defuse_abort(events),
+            "bomb_defused" => self.defused(player.or(self.defuser), events),
+            "bomb_exploded" => self.exploded(events),
+            _ => {}
+        }
+    }
+
+    /// remembers which trigger of the game events belongs to the site of the planted bomb
+    fn learn_site(&mut self) {
+        if let (Some(trigger), Some(site)) = (self.plant_trigger, self.site)
+            && !self.site_triggers.iter().any(|(t, _)| *t == trigger)
+        {
+            self.site_triggers.push((trigger, site));
+        }
+    }
+
+    fn site_of_trigger(&self, trigger: Option<i64>) -> Option<BombSite> {
+        let trigger = trigger?;
+        self.site_triggers
+            .iter()
+            .find(|(t, _)| *t == trigger)
+            .map(|(_, site)| *site)
+    }
+
+    fn pickup(&mut self, player: EntityHandle<CCSPlayerController>, events: &mut PendingEvents) {
+        if self.carrier == Some(player)
+            || !matches!(
+                self.phase,
+                BombPhase::None | BombPhase::Carried | BombPhase::Dropped
+            )
+        {
+            return;
+        }
+
+        self.carrier = Some(player);
+        self.phase = BombPhase::Carried;
+        self.position = None;
+        events.push(BombPickupEvent { player });
+    }
+
+    fn drop(&mut self, position: Option<Vector3>, events: &mut PendingEvents) {
+        let Some(player) = self.carrier else {
+            return;
+        };
+
+        // the carrier loses the bomb when the plant completes
+        if self.phase != BombPhase::Carried {
+            return;
+        }
+
+        self.carrier = None;
+        self.phase = BombPhase::Dropped;
+        self.position = position.clone();
+        events.push(BombDropEvent { player, position });
+    }
+
+    fn plant_begin(
+        &mut self,
+        player: EntityHandle<CCSPlayerController>,
+        trigger: Option<i64>,
+        events: &mut PendingEvents,
+    ) {
+        if self.phase != BombPhase::Carried && self.phase != BombPhase::None {
+            return;
+        }
+
+        let site = self.site_of_trigger(trigger);
+        self.phase = BombPhase::Planting;
+        self.carrier = Some(player);
+        self.planter = Some(player);
+        events.push(BombPlantBeginEvent { player, site });
+    }
+
+    fn plant_abort(&mut self, trigger: Option<i64>, events: &mut PendingEvents) {
+        if self.phase != BombPhase::Planting {
+            return;
+        }
+
+        let Some(player) = self.planter.take() else {
+            return;
+        };
+
+        self.phase = BombPhase::Carried;
+        events.push(BombPlantAbortEvent {
+            player,
+            site: self.site_of_trigger(trigger),
+        });
+    }
+
+    fn planted(
+        &mut self,
+        player: Option<EntityHandle<CCSPlayerController>>,
+        tick: u32,
+        events: &mut PendingEvents,
+    ) {
+        if !matches!(
+            self.phase,
+            BombPhase::None | BombPhase::Carried | BombPhase::Dropped | BombPhase::Planting
+        ) {
+            return;
+        }
+
+        self.phase = BombPhase::Planted;
+        self.carrier = None;
+        self.planter = player;
+        self.planted_tick = Some(tick);
+        events.push(BombPlantedEvent {
+            player: player.unwrap_or(EntityHandle::INVALID),
+            site: self.site,
+            position: self.position.clone().unwrap_or_default(),
+            timer_length: self.timer_length,
+        });
+    }
+
+    fn defuse_begin(
+        &mut self,
+        player: EntityHandle<CCSPlayerController>,
+        has_kit: bool,
+        tick: u32,
+        events: &mut PendingEvents,
+    ) {
+        if self.phase != BombPhase::Planted {
+            return;
+        }
+
+        self.phase = BombPhase::Defusing;
+        self.defuser = Some(player);
+        self.defuser_has_kit = has_kit;
+        self.defuse_start_tick = Some(tick);
+        events.push(BombDefuseBeginEvent { player, has_kit });
+    }
+
+    fn defuse_abort(&mut self, events: &mut PendingEvents) {
+        if self.phase != BombPhase::Defusing {
+            return;
+        }
+
+        self.phase = BombPhase::Planted;
+        self.defuse_start_tick = None;
+        if let Some(player) = self.defuser.take() {
+            events.push(BombDefuseAbortEvent {
+                player,
+                has_kit: self.defuser_has_kit,
+            });
+        }
+    }
+
+    fn defused(
+        &mut self,
+        player: Option<EntityHandle<CCSPlayerController>>,
+        events: &mut PendingEvents,
+    ) {
+        if !matches!(self.phase, BombPhase::Planted | BombPhase::Defusing) {
+            return;
+        }
+
+        self.phase = BombPhase::Defused;
+        self.defuser = player;
+        events.push(BombDefusedEvent {
+            player: player.unwrap_or(EntityHandle::INVALID),
+            site: self.site,
+        });
+    }
+
+    fn exploded(&mut self, events: &mut PendingEvents) {
+        if !matches!(self.phase, BombPhase::Planted | BombPhase::Defusing) {
+            return;
+        }
+
+        self.phase = BombPhase::Exploded;
+        events.push(BombExplodedEvent { site: self.site });
+    }
+}
@@ -0,0 +1,325 @@
+use crate::entity::{
+    EntityClass, list::EntityHandle, position::Position, serializer::vector::Vector3,
+};
+
+/// team number as networked by `m_iTeamNum`
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+pub enum Team {
+    #[default]
+    Unassigned = 0,
+    Spectator = 1,
+    Terrorist = 2,
+    CounterTerrorist = 3,
+}
+
+impl Team {
+    /// true for terrorists and counter-terrorists
+    pub fn is_playing(self) -> bool {
+        matches!(self, Team::Terrorist | Team::CounterTerrorist)
+    }
+
+    /// the other playing team, None for spectators and unassigned players
+    pub fn opponent(self) -> Option<Team> {
+        match self {
+            Team::Terrorist => Some(Team::CounterTerrorist),
+            Team::CounterTerrorist => Some(Team::Terrorist),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<u64> for Team {
+    type Error = u64;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Team::Unassigned,
+            1 => Team::Spectator,
+            2 => Team::Terrorist,
+            3 => Team::CounterTerrorist,
+            _ => return Err(value),
+        })
+    }
+}
+
+/// `m_gamePhase` of `CCSGameRules`
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+pub enum GamePhase {
+    #[default]
+    WarmupRound = 0,
+    PlayingStandard = 1,
+    PlayingFirstHalf = 2,
+    PlayingSecondHalf = 3,
+    Halftime = 4,
+    MatchEnded = 5,
+}
+
+impl TryFrom<u64> for GamePhase {
+    type Error = u64;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => GamePhase::WarmupRound,
+            1 => GamePhase::PlayingStandard,
+            2 => GamePhase::PlayingFirstHalf,
+            3 => GamePhase::PlayingSecondHalf,
+            4 => GamePhase::Halftime,
+            5 => GamePhase::MatchEnded,
+            _ => return Err(value),
+        })
+    }
+}
+
+/// `m_eRoundWinReason` of `CCSGameRules` and `reason` of the `round_end` game event
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+pub enum RoundEndReason {
+    #[default]
+    StillInProgress = 0,
+    TargetBombed = 1,
+    VipEscaped = 2,
+    VipKilled = 3,
+    TerroristsEscaped = 4,
+    CtStoppedEscape = 5,
+    TerroristsStopped = 6,
+    BombDefused = 7,
+    CtWin = 8,
+    TerroristsWin = 9,
+    Draw = 10,
+    HostagesRescued = 11,
+    TargetSaved = 12,
+    HostagesNotRescued = 13,
+    TerroristsNotEscaped = 14,
+    VipNotEscaped = 15,
+    GameStart = 16,
+    TerroristsSurrender = 17,
+    CtSurrender = 18,
+    TerroristsPlanted = 19,
+    CtsReachedHostage = 20,
+}
+
+impl TryFrom<u64> for RoundEndReason {
+    type Error = u64;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        use RoundEndReason::*;
+
+        Ok(match value {
+            0 => StillInProgress,
+            1 => TargetBombed,
+            2 => VipEscaped,
+            3 => VipKilled,
+            4 => TerroristsEscaped,
+            5 => CtStoppedEscape,
+            6 => TerroristsStopped,
+            7 => BombDefused,
+            8 => CtWin,
+            9 => TerroristsWin,
+            10 => Draw,
+            11 => HostagesRescued,
+            12 => TargetSaved,
+            13 => HostagesNotRescued,
+            14 => TerroristsNotEscaped,
+            15 => VipNotEscaped,
+            16 => GameStart,
+            17 => TerroristsSurrender,
+            18 => CtSurrender,
+            19 => TerroristsPlanted,
+            20 => CtsReachedHostage,
+            _ => return Err(value),
+        })
+    }
+}
+
+#[derive(EntityClass, Clone, Default, Debug)]
+pub struct CCSPlayerController {
+    #[entity(name = "m_iszPlayerName")]
+    pub player_name: String,
+    #[entity(name = "m_steamID")]
+    pub steam_id: u64,
+    #[entity(name = "m_iTeamNum", as_enum)]
+    pub team: Team,
+    #[entity(name = "m_iConnected")]
+    pub connected: i32,
+    #[entity(name = "m_iScore")]
+    pub score: i32,
+    #[entity(name = "m_hPlayerPawn")]
+    pub player_pawn: EntityHandle<CCSPlayerPawn>,
+    #[entity(name = "m_bPawnIsAlive")]
+    pub pawn_is_alive: bool,
+    #[entity(name = "m_iPawnHealth")]
+    pub pawn_health: u32,
+    #[entity(name = "m_iPawnArmor")]
+    pub pawn_armor: i32,
+    #[entity(name = "m_bPawnHasHelmet")]
+    pub pawn_has_helmet: bool,
+    #[entity(name = "m_bPawnHasDefuser")]
+    pub pawn_has_defuser: bool,
+    #[entity(name = "m_pInGameMoneyServices.m_iAccount")]
+    pub account: i32,
+    /// money at the start of the round
+    #[entity(name = "m_pInGameMoneyServices.m_iStartAccount")]
+    pub start_account: i32,
+    #[entity(name = "m_pInGameMoneyServices.m_iCashSpentThisRound")]
+    pub cash_spent_this_round: i32,
+    #[entity(name = "m_pInGameMoneyServices.m_iTotalCashSpent")]
+    pub total_cash_spent: i32,
+}
+
+#[derive(EntityClass, Clone, Default, Debug)]
+pub struct CCSPlayerPawn {
+    #[entity(name = "m_iTeamNum", as_enum)]
+    pub team: Team,
+    #[entity(name = "m_hController")]
+    pub controller: EntityHandle<CCSPlayerController>,
+    #[entity(name = "m_iHealth")]
+    pub health: i32,
+    #[entity(name = "m_ArmorValue")]
+    pub armor: i32,
+    #[entity(name = "m_lifeState")]
+    pub life_state: u8,
+    #[entity(name = "m_unCurrentEquipmentValue")]
+    pub current_equipment_value: u16,
+    #[entity(name = "m_unRoundStartEquipmentValue")]
+    pub round_start_equipment_value: u16,
+    #[entity(name = "m_unFreezetimeEndEquipmentValue")]
+    pub freezetime_end_equipment_value: u16,
+    /// the bomb is listed as well but decoded as `CC4`
+    #[entity(name = "m_pWeaponServices.m_hMyWeapons")]
+    pub weapons: Vec<EntityHandle<CCSWeaponBase>>,
+    #[entity(name = "m_pWeaponServices.m_hActiveWeapon")]
+    pub active_weapon: EntityHandle<CCSWeaponBase>,
+    #[entity(flatten)]
+    pub position: Position,
+}
+
+#[derive(EntityClass, Clone, Default, Debug)]
+pub struct CCSTeam {
+    #[entity(name = "m_iTeamNum", as_enum)]
+    pub team: Team,
+    #[entity(name = "m_szTeamname")]
+    pub team_name: String,
+    #[entity(name = "m_szClanTeamname")]
+    pub clan_name: String,
+    #[entity(name = "m_iScore")]
+    pub score: i32,
+    #[entity(name = "m_scoreFirstHalf")]
+    pub score_first_half: i32,
+    #[entity(name = "m_scoreSecondHalf")]
+    pub score_second_half: i32,
+    #[entity(name = "m_scoreOvertime")]
+    pub score_overtime: i32,
+    #[entity(name = "m_bSurrendered")]
+    pub surrendered: bool,
+}
+
+/// the game rules are networked through the pointer of their proxy entity
+#[derive(EntityClass, Clone, Default, Debug)]
+pub struct CCSGameRulesProxy {
+    #[entity(name = "m_pGameRules.m_totalRoundsPlayed")]
+    pub total_rounds_played: i32,
+    #[entity(name = "m_pGameRules.m_gamePhase", as_enum)]
+    pub game_phase: GamePhase,
+    #[entity(name = "m_pGameRules.m_bWarmupPeriod")]
+    pub warmup_period: bool,
+    #[entity(name = "m_pGameRules.m_bFreezePeriod")]
+    pub freeze_period: bool,
+    #[entity(name = "m_pGameRules.m_bHasMatchStarted")]
+    pub has_match_started: bool,
+    /// number of the running overtime, zero during regulation
+    #[entity(name = "m_pGameRules.m_nOvertimePlaying")]
+    pub overtime_playing: i32,
+    /// winning team number, zero while the round is in progress and one for draws
+    #[entity(name = "m_pGameRules.m_iRoundWinStatus")]
+    pub round_win_status: i32,
+    #[entity(name = "m_pGameRules.m_eRoundWinReason", as_enum)]
+    pub round_win_reason: RoundEndReason,
+    /// loss bonus level of the counter-terrorists, lowered by one on a win
+    #[entity(name = "m_pGameRules.m_iNumConsecutiveCTLoses")]
+    pub consecutive_ct_losses: i32,
+    #[entity(name = "m_pGameRules.m_iNumConsecutiveTerroristLoses")]
+    pub consecutive_terrorist_losses: i32,
+}
+
+/// fields shared by every weapon, embed it with `#[entity(flatten)]` into weapon classes
+#[derive(EntityClass, Clone, Default, Debug)]
+pub struct WeaponBase {
+    /// pawn of the holder, invalid while the weapon lies on the ground
+    #[entity(name = "m_hOwnerEntity")]
+    pub owner: EntityHandle<CCSPlayerPawn>,
+    /// see `game_state::inventory::item_definition_name`
+    #[entity(name = "m_AttributeManager.m_Item.m_iItemDefinitionIndex")]
+    pub item_definition_index: u16,
+    /// -1 for weapons without a clip, e.g. knives
+    #[entity(name = "m_iClip1")]
+    pub clip: i32,
+    /// primary and secondary reserve ammo
+    #[entity(name = "m_pReserveAmmo")]
+    pub reserve_ammo: Vec<i32>,
+}
+
+/// shared by every weapon class except the bomb, e.g. `CAK47` and `CWeaponAWP`
+#[derive(EntityClass, Clone, Default, Debug)]
+pub struct CCSWeaponBase {
+    #[entity(flatten)]
+    pub weapon: WeaponBase,
+    #[entity(flatten)]
+    pub position: Position,
+}
+
+#[derive(EntityClass, Clone, Default, Debug)]
+pub struct CC4 {
+    /// the owner is the carrier
+    #[entity(flatten)]
+    pub weapon: WeaponBase,
+    #[entity(name = "m_bStartedArming")]
+    pub started_arming: bool,
+    #[entity(flatten)]
+    pub position: Position,
+}
+
+#[derive(EntityClass, Clone, Default, Debug)]
+pub struct CPlantedC4 {
+    #[entity(name = "m_bBombTicking")]
+    pub bomb_ticking: bool,
+    /// zero for A, one for B
+    #[entity(name = "m_nBombSite")]
+    pub bomb_site: i32,
+    #[entity(name = "m_flTimerLength")]
+    pub timer_length: f32,
+    #[entity(name = "m_flDefuseLength")]
+    pub defuse_length: f32,
+    #[entity(name = "m_bBeingDefused")]
+    pub being_defused: bool,
+    #[entity(name = "m_hBombDefuser")]
+    pub bomb_defuser: EntityHandle<CCSPlayerPawn>,
+    #[entity(name = "m_bBombDefused")]
+    pub bomb_defused: bool,
+    #[entity(name = "m_bHasExploded")]
+    pub has_exploded: bool,
+    #[entity(flatten)]
+    pub position: Position,
+}
+
+/// shared by every `C*Projectile` class, e.g. `CSmokeGrenadeProjectile`
+#[derive(EntityClass, Clone, Default, Debug)]
+pub struct CBaseCSGrenadeProjectile {
+    #[entity(name = "m_hThrower")]
+    pub thrower: EntityHandle<CCSPlayerPawn>,
+    #[entity(flatten)]
+    pub position: Position,
+}
+
+/// fire of a molotov or incendiary, the flame arrays have a fixed size
+#[derive(EntityClass, Clone, Default, Debug)]
+pub struct CInferno {
+    /// pawn of the thrower
+    #[entity(name = "m_hOwnerEntity")]
+    pub owner: EntityHandle<CCSPlayerPawn>,
+    #[entity(name = "m_firePositions")]
+    pub fire_positions: Vec<Vector3>,
+    #[entity(name = "m_bFireIsBurning")]
+    pub fire_is_burning: Vec<bool>,
+    /// number of spawned flames, the entries after it are unused
+    #[entity(name = "m_fireCount")]
+    pub fire_count: i32,
+}
@@ -0,0 +1,498 @@
+use foldhash::{HashMap, HashMapExt};
+
+use crate::{
+    entity::list::EntityHandle,
+    game_state::{
+        GameEventKeys, PendingEvents, Player,
+        classes::{CCSGameRulesProxy, CCSPlayerController},
+    },
+};
+
+/// `hitgroup` of `player_hurt`
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+pub enum HitGroup {
+    /// fire, explosions, falls and unknown groups
+    #[default]
+    Generic = 0,
+    Head = 1,
+    Chest = 2,
+    Stomach = 3,
+    LeftArm = 4,
+    RightArm = 5,
+    LeftLeg = 6,
+    RightLeg = 7,
+    Neck = 8,
+    Gear = 10,
+}
+
+impl TryFrom<u64> for HitGroup {
+    type Error = u64;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => HitGroup::Generic,
+            1 => HitGroup::Head,
+            2 => HitGroup::Chest,
+            3 => HitGroup::Stomach,
+            4 => HitGroup::LeftArm,
+            5 => HitGroup::RightArm,
+            6 => HitGroup::LeftLeg,
+            7 => HitGroup::RightLeg,
+            8 => HitGroup::Neck,
+            10 => HitGroup::Gear,
+            _ => return Err(value),
+        })
+    }
+}
+
+/// what dealt the damage, derived from the `weapon` of `player_hurt`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DamageKind {
+    Gun,
+    /// grenades and the fire of molotovs and incendiaries
+    Utility,
+    Melee,
+    /// falls, the bomb and anything else without a weapon
+    Other,
+}
+
+impl DamageKind {
+    fn from_weapon_name(weapon: &str) -> Self {
+        match weapon {
+            "hegrenade" | "inferno" | "molotov" | "incgrenade" | "flashbang" | "smokegrenade"
+            | "decoy" => DamageKind::Utility,
+            "" | "world" | "worldspawn" | "planted_c4" | "c4" => DamageKind::Other,
+            _ if weapon.starts_with("knife") || weapon == "bayonet" => DamageKind::Melee,
+            _ => DamageKind::Gun,
+        }
+    }
+}
+
+/// a single `player_hurt`
+#[derive(Clone, Debug)]
+pub struct Damage {
+    pub tick: u32,
+    pub round: i32,
+    /// invalid for damage without an attacking player, e.g. falls
+    pub attacker: EntityHandle<CCSPlayerController>,
+    pub victim: EntityHandle<CCSPlayerController>,
+    /// name without the `weapon_` prefix, e.g. `ak47` or `inferno`
+    pub weapon: String,
+    pub kind: DamageKind,
+    pub hitgroup: HitGroup,
+    /// health damage capped at the health of the victim before the hit
+    pub health: i32,
+    /// health damage as reported, may exceed the health of the victim
+    pub health_uncapped: i32,
+    pub armor: i32,
+    /// health of the victim after the hit
+    pub remaining_health: i32,
+    /// damage to a teammate or to the attacker itself
+    pub is_team_damage: bool,
+}
+
+/// notifies every recorded `player_hurt`
+pub struct PlayerDamageEvent {
+    pub damage: Damage,
+}
+
+game_state_event!(PlayerDamageEvent);
+
+/// game events recorded by the damage ledger
+pub(crate) const DAMAGE_GAME_EVENTS: [&str; 1] = ["player_hurt"];
+
+/// sums of damage
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct DamageTotals {
+    /// capped health damage, the one counted by ADR
+    pub health: i32,
+    pub health_uncapped: i32,
+    pub armor: i32,
+    pub hits: u32,
+}
+
+impl DamageTotals {
+    fn add(&mut self, damage: &Damage) {
+        self.health += damage.health;
+        self.health_uncapped += damage.health_uncapped;
+        self.armor += damage.armor;
+        self.hits += 1;
+    }
+}
+
+/// the damage of the match outside of warmup,
+/// cleared once the match restarts
+///
+/// the aggregates only count damage of players to their opponents, like ADR does,
+/// read `entries` for team damage and damage of the world
+#[derive(Clone, Debug, Default)]
+pub struct DamageLedger {
+    entries: Vec<Damage>,
+
+    /// the round the health of the victims belongs to
+    pub(crate) round: i32,
+    /// health of the players hurt in this round after the last hit
+    health: Vec<(EntityHandle<CCSPlayerController>, i32)>,
+}
+
+/// health of a pawn at the start of a round
+const MAX_HEALTH: i32 = 100;
+
+impl DamageLedger {
+    /// every recorded hit in order
+    pub fn entries(&self) -> &[Damage] {
+        &self.entries
+    }
+
+    /// damage of the attacker to opponents
+    pub fn total(&self, attacker: EntityHandle<CCSPlayerController>) -> DamageTotals {
+        self.sum(|d| d.attacker == attacker)
+    }
+
+    /// damage of the attacker to opponents of the given kind,
+    /// e.g. `DamageKind::Utility` for utility damage
+    pub fn total_by_kind(
+        &self,
+        attacker: EntityHandle<CCSPlayerController>,
+        kind: DamageKind,
+    ) -> DamageTotals {
+        self.sum(|d| d.attacker == attacker && d.kind == kind)
+    }
+
+    /// average capped health damage of the attacker per round
+    pub fn average_per_round(
+        &self,
+        attacker: EntityHandle<CCSPlayerController>,
+        rounds: i32,
+    ) -> f32 {
+        if rounds <= 0 {
+            return 0.0;
+        }
+
+        self.total(attacker).health as f32 / rounds as f32
+    }
+
+    /// damage of every attacker to every victim
+    pub fn by_attacker_and_victim(
+        &self,
+    ) -> HashMap<
+        (
+            EntityHandle<CCSPlayerController>,
+            EntityHandle<CCSPlayerController>,
+        ),
+        DamageTotals,
+    > {
+        self.group_by(|_| true, |d| (d.attacker, d.victim))
+    }
+
+    /// damage of the attacker by the name of the weapon
+    pub fn by_weapon(
+        &self,
+        attacker: EntityHandle<CCSPlayerController>,
+    ) -> HashMap<&str, DamageTotals> {
+        self.group_by(|d| d.attacker == attacker, |d| d.weapon.as_str())
+    }
+
+    pub fn by_hitgroup(
+        &self,
+        attacker: EntityHandle<CCSPlayerController>,
+    ) -> HashMap<HitGroup, DamageTotals> {
+        self.group_by(|d| d.attacker == attacker, |d| d.hitgroup)
+    }
+
+    /// damage of the attacker by the one based round number
+    pub fn by_round(
+        &self,
+        attacker: EntityHandle<CCSPlayerController>,
+    ) -> HashMap<i32, DamageTotals> {
+        self.group_by(|d| d.attacker == attacker, |d| d.round)
+    }
+
+    fn counted(&self) -> impl Iterator<Item = &Damage> {
+        self.entries
+            .iter()
+            .filter(|d| d.attacker.is_valid() && !d.is_team_damage)
+    }
+
+    fn sum(&self, filter: impl Fn(&Damage) -> bool) -> DamageTotals {
+        let mut totals = DamageTotals::default();
+        for damage in self.counted().filter(|d| filter(d)) {
+            totals.add(damage);
+        }
+        totals
+    }
+
+    fn group_by<'a, K: Eq + std::hash::Hash>(
+        &'a self,
+        filter: impl Fn(&Damage) -> bool,
+        key: impl Fn(&'a Damage) -> K,
+    ) -> HashMap<K, DamageTotals> {
+        let mut groups = HashMap::new();
+        for damage in self.counted().filter(|d| filter(d)) {
+            groups
+                .entry(key(damage))
+                .or_insert_with(DamageTotals::default)
+                .add(damage);
+        }
+        groups
+    }
+
+    /// drops the entries of the restarted match or round
+    pub(crate) fn restart(&mut self, round: i32) {
+        self.entries.clear();
+        self.reset(round);
+    }
+
+    /// forgets the health of the previous round, the entries are dropped if the match restarted
+    pub(crate) fn reset(&mut self, round: i32) {
+        if round < self.round {
+            self.entries.clear();
+        }
+
+        self.round = round;
+        self.health.clear();
+    }
+
+    pub(crate) fn on_game_event(
+        &mut self,
+        name: &str,
+        keys: &GameEventKeys<'_>,
+        players: &[Player],
+        rules: &CCSGameRulesProxy,
+        tick: u32,
+        events: &mut PendingEvents,
+    ) {
+        if name != "player_hurt" || rules.warmup_period {
+            return;
+        }
+
+        let player_by_slot = |key| {
+            keys.int(key)
+                .and_then(|slot| players.iter().find(|p| p.slot() as i64 == slot))
+        };
+
+        let Some(victim) = player_by_slot("userid") else {
+            return;
+        };
+        let attacker = player_by_slot("attacker");
+
+        let remaining_health = keys.int("health").unwrap_or_default().max(0) as i32;
+        let health_uncapped = keys.int("dmg_health").unwrap_or_default().max(0) as i32;
+
+        // the snapshot of the victim may already contain the hit, the last hit never does
+        let health_before = match self
+            .health
+            .iter_mut()
+            .find(|(h, _)| *h == victim.controller)
+        {
+            Some((_, health)) => std::mem::replace(health, remaining_health),
+            None => {
+                self.health.push((victim.controller, remaining_health));
+                if victim.health > remaining_health as u32 {
+                    victim.health as i32
+                } else {
+                    MAX_HEALTH
+                }
+            }
+        };
+        let health = if remaining_health > 0 {
+            health_uncapped
+        } else {
+            health_uncapped.min(health_before)
+        };
+
+        let weapon = keys.string("weapon").unwrap_or_default();
+        let weapon = weapon.strip_prefix("weapon_").unwrap_or(weapon).to_string();
+
+        let damage = Damage {
+            tick,
+            round: self.round,
+            attacker: attacker.map_or(EntityHandle::INVALID, |p| p.controller),
+            victim: victim.controller,
+            kind: DamageKind::from_weapon_name(&weapon),
+            weapon,
+            hitgroup: keys
+                .int("hitgroup")
+                .and_then(|v| HitGroup::try_from(v as u64).ok())
+                .unwrap_or_default(),
+            health,
+            health_uncapped,
+            armor: keys.int("dmg_armor").unwrap_or_default().max(0) as i32,
+            remaining_health,
+            is_team_damage: attacker.is_some_and(|a| {
+                a.controller == victim.controller || (a.team.is_playing() && a.team == victim.team)
+            }),
+        };
+
+        events.push(PlayerDamageEvent {
+            damage: damage.clone(),
+        });
+        self.entries.push(damage);
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use crate::{game_event::derive::KeyT, game_state::classes::Team};
+
+    /// a connected player with the controller in the slot, shared with the other trackers
+    pub(in crate::game_state) fn player(slot: u32, team: Team, health: u32) -> Player {
+        Player {
+            controller: EntityHandle::from_raw((slot + 1) as u64 | 1 << 14),
+            pawn: EntityHandle::INVALID,
+            name: String::new(),
+            steam_id: 0,
+            team,
+            is_connected: true,
+            is_alive: health > 0,
+            health,
+            armor: 0,
+            has_helmet: false,
+            has_defuser: false,
+            score: 0,
+            position: None,
+            money: 0,
+            start_money: 0,
+            cash_spent_this_round: 0,
+            total_cash_spent: 0,
+            equipment_value: 0,
+            round_start_equipment_value: 0,
+            freeze_time_end_equipment_value: 0,
+        }
+    }
+
+    /// records a `player_hurt` of the attacker in slot 0 on the victim in slot 1
+    fn hurt(
+        ledger: &mut DamageLedger,
+        players: &[Player],
+        rules: &CCSGameRulesProxy,
+        remaining_health: i32,
+        dmg_health: i32,
+    ) {
+        let names = ["userid", "attacker", "health", "dmg_health", "weapon"].map(String::from);
+        let int = |value| KeyT {
+            val_short: Some(value),
+            ..Default::default()
+        };
+        let keys = [
+            int(1),
+            int(0),
+            int(remaining_health),
+            int(dmg_health),
+            KeyT {
+                val_string: Some("weapon_ak47".to_string()),
+                ..Default::default()
+            },
+        ];
+
+        ledger.on_game_event(
+            "player_hurt",
+            &GameEventKeys {
+                names: &names,
+                keys: &keys,
+            },
+            players,
+            rules,
+            0,
+            &mut PendingEvents::default(),
+        );
+    }
+
+    fn health(ledger: &DamageLedger) -> Vec<i32> {
+        ledger.entries().iter().map(|d| d.health).collect()
+    }
+
+    #[test]
+    fn caps_the_lethal_hit_at_the_health_left() {
+        let players = [
+            player(0, Team::Terrorist, 100),
+            player(1, Team::CounterTerrorist, 100),
+        ];
+        let mut ledger = DamageLedger::default();
+
+        hurt(&mut ledger, &players, &Default::default(), 73, 27);
+        hurt(&mut ledger, &players, &Default::default(), 0, 108);
+
+        assert_eq!(health(&ledger), [27, 73]);
+        assert_eq!(ledger.entries()[1].health_uncapped, 108);
+        assert_eq!(ledger.total(players[0].controller).health, 100);
+    }
+
+    #[test]
+    fn caps_the_first_hit_at_the_health_of_the_snapshot() {
+        // the snapshot of the victim was taken before the hit
+        let players = [
+            player(0, Team::Terrorist, 100),
+            player(1, Team::CounterTerrorist, 40),
+        ];
+        let mut ledger = DamageLedger::default();
+
+        hurt(&mut ledger, &players, &Default::default(), 0, 108);
+
+        assert_eq!(health(&ledger), [40]);
+    }
+
+    #[test]
+    fn caps_the_first_hit_at_max_health_if_the_snapshot_contains_it() {
+        let players = [
+            player(0, Team::Terrorist, 100),
+            player(1, Team::CounterTerrorist, 0),
+        ];
+        let mut ledger = DamageLedger::default();
+
+        hurt(&mut ledger, &players, &Default::default(), 0, 250);
+
+        assert_eq!(health(&ledger), [MAX_HEALTH]);
+    }
+
+    #[test]
+    fn keeps_non_lethal_hits_uncapped() {
+        let players = [
+            player(0, Team::Terrorist, 100),
+            player(1, Team::CounterTerrorist, 100),
+        ];
+        let mut ledger = DamageLedger::default();
+
+        hurt(&mut ledger, &players, &Default::default(), 1, 99);
+
+        assert_eq!(health(&ledger), [99]);
+    }
+
+    #[test]
+    fn forgets_the_health_of_the_previous_round() {
+        let players = [
+            player(0, Team::Terrorist, 100),
+            player(1, Team::CounterTerrorist, 100),
+        ];
+        let mut ledger = DamageLedger::default();
+        ledger.reset(1);
+
+        hurt(&mut ledger, &players, &Default::default(), 10, 90);
+        ledger.reset(2);
+        hurt(&mut ledger, &players, &Default::default(), 0, 120);
+
+        assert_eq!(health(&ledger), [90, 100]);
+        assert_eq!(ledger.by_round(players[0].controller)[&2].health, 100);
+    }
+
+    #[test]
+    fn skips_warmup_and_team_damage_in_the_aggregates() {
+        let players = [
+            player(0, Team::Terrorist, 100),
+            player(1, Team::Terrorist, 100),
+        ];
+        let mut ledger = DamageLedger::default();
+
+        let warmup = CCSGameRulesProxy {
+            warmup_period: true,
+            ..Default::default()
+        };
+        hurt(&mut ledger, &players, &warmup, 50, 50);
+        assert!(ledger.entries().is_empty());
+
+        hurt(&mut ledger, &players, &Default::default(), 50, 50);
+        assert!(ledger.entries()[0].is_team_damage);
+        assert_eq!(ledger.total(players[0].controller), DamageTotals::default());
+    }
+}
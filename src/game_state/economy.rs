@@ -0,0 +1,710 @@
+use crate::{
+    entity::list::EntityHandle,
+    game_state::{
+        GameEventKeys, PendingEvents, Player,
+        classes::{CCSGameRulesProxy, CCSPlayerController, CCSWeaponBase, GamePhase, Team},
+        inventory::InventoryWeapon,
+        round::{RoundPhase, RoundTracker},
+    },
+};
+
+/// how much a team invested into a round
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+pub enum BuyType {
+    /// the first round of a regulation half, everyone starts with the same money,
+    /// overtime halves start with more money and are classified by the equipment value
+    Pistol,
+    #[default]
+    Eco,
+    /// more than an eco, but short of a full buy
+    Force,
+    FullBuy,
+}
+
+/// average equipment value per player a team needs to leave eco
+pub const ECO_EQUIPMENT_VALUE: i32 = 2000;
+/// average equipment value per player of a full buy
+pub const FULL_BUY_EQUIPMENT_VALUE: i32 = 4000;
+
+impl BuyType {
+    /// classifies the average equipment value of the players of a team when the freeze time ended
+    pub fn classify(average_equipment_value: i32) -> Self {
+        if average_equipment_value >= FULL_BUY_EQUIPMENT_VALUE {
+            BuyType::FullBuy
+        } else if average_equipment_value >= ECO_EQUIPMENT_VALUE {
+            BuyType::Force
+        } else {
+            BuyType::Eco
+        }
+    }
+}
+
+/// a purchase of a player
+#[derive(Clone, Debug)]
+pub struct Purchase {
+    pub tick: u32,
+    pub player: EntityHandle<CCSPlayerController>,
+    /// the `weapon` of `item_purchase`, e.g. `weapon_ak47`,
+    /// None if the purchase was only observed in the spent money
+    pub item: Option<String>,
+    /// money spent, None if it was paid together with a previous purchase of the same tick
+    pub cost: Option<i32>,
+    /// the bought weapon, None for equipment like armor and until it enters the inventory
+    pub weapon: Option<EntityHandle<CCSWeaponBase>>,
+    /// the teammate who picked up the bought weapon last, e.g. after a drop for a teammate,
+    /// the buyer otherwise
+    pub receiver: EntityHandle<CCSPlayerController>,
+    /// sold back during the buy time
+    pub refunded: bool,
+}
+
+impl Purchase {
+    fn new(tick: u32, player: EntityHandle<CCSPlayerController>) -> Self {
+        Self {
+            tick,
+            player,
+            item: None,
+            cost: None,
+            weapon: None,
+            receiver: player,
+            refunded: false,
+        }
+    }
+}
+
+/// the balances of a player in a round
+#[derive(Clone, Debug)]
+pub struct PlayerRoundEconomy {
+    pub player: EntityHandle<CCSPlayerController>,
+    pub team: Team,
+    pub start_money: i32,
+    pub spent: i32,
+    /// None until the round ends
+    pub end_money: Option<i32>,
+    pub start_equipment_value: i32,
+    pub freeze_time_end_equipment_value: i32,
+}
+
+/// the balances of a team in a round, summed over its players
+#[derive(Clone, Debug)]
+pub struct TeamRoundEconomy {
+    pub team: Team,
+    pub start_money: i32,
+    pub spent: i32,
+    pub end_money: Option<i32>,
+    pub freeze_time_end_equipment_value: i32,
+    /// money each player receives if the team loses this round with the default cash convars,
+    /// servers with other `cash_team_loser_bonus*` values pay a different bonus
+    pub default_loss_bonus: i32,
+    pub buy_type: BuyType,
+}
+
+/// the economy of a round outside of warmup
+#[derive(Clone, Debug)]
+pub struct RoundEconomy {
+    /// one based number of the round
+    pub round: i32,
+    pub players: Vec<PlayerRoundEconomy>,
+    /// terrorists followed by counter-terrorists
+    pub teams: [TeamRoundEconomy; 2],
+    pub purchases: Vec<Purchase>,
+    /// the first round of a regulation half
+    pub pistol: bool,
+    pub ended: bool,
+}
+
+impl RoundEconomy {
+    fn new(round: i32, pistol: bool) -> Self {
+        let team = |team| TeamRoundEconomy {
+            team,
+            start_money: 0,
+            spent: 0,
+            end_money: None,
+            freeze_time_end_equipment_value: 0,
+            default_loss_bonus: 0,
+            buy_type: BuyType::default(),
+        };
+
+        Self {
+            round,
+            players: Vec::new(),
+            teams: [team(Team::Terrorist), team(Team::CounterTerrorist)],
+            purchases: Vec::new(),
+            pistol,
+            ended: false,
+        }
+    }
+
+    pub fn player(&self, player: EntityHandle<CCSPlayerController>) -> Option<&PlayerRoundEconomy> {
+        self.players.iter().find(|p| p.player == player)
+    }
+
+    /// None for spectators and unassigned players
+    pub fn team(&self, team: Team) -> Option<&TeamRoundEconomy> {
+        self.teams.iter().find(|t| t.team == team)
+    }
+}
+
+/// notifies when a purchase is observed, the item, the cost or the weapon
+/// may be completed by `item_purchase`, the money or the inventory of the same tick
+pub struct PurchaseEvent {
+    pub purchase: Purchase,
+}
+
+/// notifies when the spent money of a player drops by the cost of one of their purchases
+pub struct PurchaseRefundEvent {
+    pub purchase: Purchase,
+}
+
+/// notifies once per round when the round ends with the final balances
+pub struct RoundEconomyEvent {
+    pub economy: RoundEconomy,
+}
+
+game_state_event!(PurchaseEvent, PurchaseRefundEvent, RoundEconomyEvent);
+
+/// game events naming the purchases
+pub(crate) const ECONOMY_GAME_EVENTS: [&str; 1] = ["item_purchase"];
+
+/// money of the losing team with the default `cash_team_loser_bonus`,
+/// the convars are not part of the networked entities
+const LOSS_BONUS: i32 = 1400;
+/// default `cash_team_loser_bonus_consecutive_rounds`
+const LOSS_BONUS_INCREMENT: i32 = 500;
+/// the loss bonus stops increasing after this many consecutive losses
+const MAX_LOSS_BONUS_LEVEL: i32 = 4;
+
+/// balances and purchases of every round outside of warmup,
+/// cleared once the match restarts
+///
+/// the balances are read from the money services of the controllers
+/// and the equipment values of the pawns,
+/// purchases are paired from `item_purchase`, the increases of the spent money
+/// and the weapons entering the inventory of the buyer in the same tick,
+/// a decrease of the spent money refunds the latest purchase with the same cost
+#[derive(Clone, Debug, Default)]
+pub struct Economy {
+    rounds: Vec<RoundEconomy>,
+    /// the spent money of each player in the last update
+    spent: Vec<(EntityHandle<CCSPlayerController>, i32)>,
+    /// weapons picked up in this tick which are not paired with a purchase yet
+    pickups: Vec<(u32, EntityHandle<CCSPlayerController>, InventoryWeapon)>,
+    /// the halftime passed since the last round started
+    halftime: bool,
+}
+
+impl Economy {
+    /// every round in order, the last one may still be running
+    pub fn rounds(&self) -> &[RoundEconomy] {
+        &self.rounds
+    }
+
+    /// the economy of the round with the one based number
+    pub fn round(&self, round: i32) -> Option<&RoundEconomy> {
+        self.rounds.iter().rev().find(|r| r.round == round)
+    }
+
+    /// the running round, None during warmup
+    pub fn current(&self) -> Option<&RoundEconomy> {
+        self.rounds.last().filter(|r| !r.ended)
+    }
+
+    /// every purchase of the player in order
+    pub fn purchases(
+        &self,
+        player: EntityHandle<CCSPlayerController>,
+    ) -> impl Iterator<Item = &Purchase> {
+        self.rounds
+            .iter()
+            .flat_map(|r| r.purchases.iter())
+            .filter(move |p| p.player == player)
+    }
+
+    /// drops the rounds of the restarted match or round
+    pub(crate) fn restart(&mut self) {
+        *self = Self::default();
+    }
+
+    pub(crate) fn on_entities_updated(
+        &mut self,
+        players: &[Player],
+        rules: &CCSGameRulesProxy,
+        round: &RoundTracker,
+        pickups: &[(EntityHandle<CCSPlayerController>, InventoryWeapon)],
+        tick: u32,
+        events: &mut PendingEvents,
+    ) {
+        if rules.warmup_period {
+            return;
+        }
+
+        // a restart resets the round number
+        if self
+            .rounds
+            .last()
+            .is_some_and(|last| round.round < last.round)
+        {
+            self.restart();
+        }
+
+        if rules.game_phase == GamePhase::Halftime {
+            self.halftime = true;
+        }
+
+        if self
+            .rounds
+            .last()
+            .is_none_or(|last| last.round != round.round)
+            && matches!(round.phase, RoundPhase::FreezeTime | RoundPhase::Live)
+        {
+            // overtime starts with more money, its halves have no pistol round
+            let pistol = round.round == 1 || (self.halftime && rules.overtime_playing == 0);
+            self.rounds.push(RoundEconomy::new(round.round, pistol));
+            self.spent.clear();
+            self.halftime = false;
+        }
+
+        let Some(economy) = self.rounds.last_mut().filter(|r| !r.ended) else {
+            return;
+        };
+
+        let ended = matches!(round.phase, RoundPhase::Ended | RoundPhase::OfficiallyEnded);
+
+        self.pickups.retain(|(t, _, _)| *t == tick);
+        for (player, weapon) in pickups {
+            // a bought weapon changing hands within the team
+            if let Some(purchase) = economy
+                .purchases
+                .iter_mut()
+                .find(|p| p.weapon == Some(weapon.entity))
+            {
+                let team = |controller| {
+                    players
+                        .iter()
+                        .find(|p| p.controller == controller)
+                        .map(|p| p.team)
+                };
+                if team(*player).is_some() && team(*player) == team(purchase.player) {
+                    purchase.receiver = *player;
+                }
+                continue;
+            }
+
+            // `item_purchase` may arrive after the weapon
+            match economy.purchases.iter_mut().find(|p| {
+                p.player == *player
+                    && p.tick == tick
+                    && p.weapon.is_none()
+                    && p.item
+                        .as_deref()
+                        .is_some_and(|item| Some(item) == weapon.name)
+            }) {
+                Some(purchase) => purchase.weapon = Some(weapon.entity),
+                None => self.pickups.push((tick, *player, weapon.clone())),
+            }
+        }
+
+        for player in players.iter().filter(|p| p.is_playing()) {
+            let idx = match economy
+                .players
+                .iter()
+                .position(|p| p.player == player.controller)
+            {
+                Some(idx) => idx,
+                None => {
+                    economy.players.push(PlayerRoundEconomy {
+                        player: player.controller,
+                        team: player.team,
+                        start_money: player.start_money,
+                        spent: 0,
+                        end_money: None,
+                        start_equipment_value: player.round_start_equipment_value,
+                        freeze_time_end_equipment_value: 0,
+                    });
+                    economy.players.len() - 1
+                }
+            };
+
+            let player_economy = &mut economy.players[idx];
+            player_economy.team = player.team;
+            player_economy.spent = player.cash_spent_this_round;
+            if round.phase != RoundPhase::FreezeTime {
+                player_economy.freeze_time_end_equipment_value =
+                    player.freeze_time_end_equipment_value;
+            }
+            if ended {
+                player_economy.end_money = Some(player.money);
+            }
+
+            let spent = match self.spent.iter_mut().find(|(p, _)| *p == player.controller) {
+                Some((_, spent)) => std::mem::replace(spent, player.cash_spent_this_round),
+                // the first observation is the baseline, e.g. after joining
+                None => {
+                    self.spent
+                        .push((player.controller, player.cash_spent_this_round));
+                    player.cash_spent_this_round
+                }
+            };
+
+            if player.cash_spent_this_round > spent {
+                let cost = player.cash_spent_this_round - spent;
+                let named = economy
+                    .purchases
+                    .iter_mut()
+                    .find(|p| p.player == player.controller && p.tick == tick && p.cost.is_none());
+
+                match named {
+                    Some(purchase) => purchase.cost = Some(cost),
+                    None => {
+                        let purchase = Purchase {
+                            cost: Some(cost),
+                            ..Purchase::new(tick, player.controller)
+                        };
+                        events.push(PurchaseEvent {
+                            purchase: purchase.clone(),
+                        });
+                        economy.purchases.push(purchase);
+                    }
+                }
+            } else if player.cash_spent_this_round < spent {
+                let refund = spent - player.cash_spent_this_round;
+                if let Some(purchase) = economy.purchases.iter_mut().rev().find(|p| {
+                    p.player == player.controller && !p.refunded && p.cost == Some(refund)
+                }) {
+                    purchase.refunded = true;
+                    events.push(PurchaseRefundEvent {
+                        purchase: purchase.clone(),
+                    });
+                }
+            }
+        }
+
+        for team in &mut economy.teams {
+            let members = economy.players.iter().filter(|p| p.team == team.team);
+
+            let mut count = 0;
+            team.start_money = 0;
+            team.spent = 0;
+            team.freeze_time_end_equipment_value = 0;
+            for member in members {
+                count += 1;
+                team.start_money += member.start_money;
+                team.spent += member.spent;
+                team.freeze_time_end_equipment_value += member.freeze_time_end_equipment_value;
+            }
+            if ended {
+                team.end_money = Some(
+                    economy
+                        .players
+                        .iter()
+                        .filter(|p| p.team == team.team)
+                        .filter_map(|p| p.end_money)
+                        .sum(),
+                );
+            }
+
+            let losses = match team.team {
+                Team::CounterTerrorist => rules.consecutive_ct_losses,
+                _ => rules.consecutive_terrorist_losses,
+            };
+            team.default_loss_bonus =
+                LOSS_BONUS + LOSS_BONUS_INCREMENT * losses.clamp(0, MAX_LOSS_BONUS_LEVEL);
+
+            team.buy_type = if economy.pistol {
+                BuyType::Pistol
+            } else {
+                BuyType::classify(team.freeze_time_end_equipment_value / count.max(1))
+            };
+        }
+
+        if ended {
+            economy.ended = true;
+            events.push(RoundEconomyEvent {
+                economy: economy.clone(),
+            });
+        }
+    }
+
+    pub(crate) fn on_game_event(
+        &mut self,
+        name: &str,
+        keys: &GameEventKeys<'_>,
+        players: &[Player],
+        tick: u32,
+        events: &mut PendingEvents,
+    ) {
+        if name != "item_purchase" {
+            return;
+        }
+
+        let Some(economy) = self.rounds.last_mut().filter(|r| !r.ended) else {
+            return;
+        };
+        let Some(player) = keys.int("userid").and_then(|slot| {
+            players
+                .iter()
+                .find(|p| p.slot() as i64 == slot)
+                .map(|p| p.controller)
+        }) else {
+            return;
+        };
+        let item = keys.string("weapon").unwrap_or_default().to_string();
+
+        // the weapon may have entered the inventory first
+        let weapon = self
+            .pickups
+            .iter()
+            .position(|(t, p, w)| *t == tick && *p == player && w.name == Some(item.as_str()))
+            .map(|idx| self.pickups.swap_remove(idx).2.entity);
+
+        // the spent money may have been observed first
+        if let Some(purchase) = economy
+            .purchases
+            .iter_mut()
+            .find(|p| p.player == player && p.tick == tick && p.item.is_none())
+        {
+            purchase.item = Some(item);
+            purchase.weapon = purchase.weapon.or(weapon);
+            return;
+        }
+
+        let purchase = Purchase {
+            item: Some(item),
+            weapon,
+            ..Purchase::new(tick, player)
+        };
+        events.push(PurchaseEvent {
+            purchase: purchase.clone(),
+        });
+        economy.purchases.push(purchase);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game_event::derive::KeyT, game_state::damage};
+
+    const AK47: u32 = 21;
+
+    fn player(spent: i32) -> Player {
+        Player {
+            cash_spent_this_round: spent,
+            ..damage::tests::player(0, Team::Terrorist, 100)
+        }
+    }
+
+    fn ak47() -> InventoryWeapon {
+        InventoryWeapon {
+            entity: EntityHandle::from_raw((AK47 | 1 << 14) as u64),
+            item_definition_index: 7,
+            name: Some("weapon_ak47"),
+            clip: 30,
+            reserve_ammo: 90,
+        }
+    }
+
+    fn round(number: i32, phase: RoundPhase) -> RoundTracker {
+        let mut round = RoundTracker::default();
+        round.round = number;
+        round.phase = phase;
+        round
+    }
+
+    fn rules() -> CCSGameRulesProxy {
+        CCSGameRulesProxy {
+            game_phase: GamePhase::PlayingFirstHalf,
+            ..Default::default()
+        }
+    }
+
+    /// updates the freeze time of the first round with the spent money and the pickups
+    fn update(
+        economy: &mut Economy,
+        spent: i32,
+        pickups: &[InventoryWeapon],
+        tick: u32,
+        events: &mut PendingEvents,
+    ) {
+        let player = player(spent);
+        let pickups: Vec<_> = pickups
+            .iter()
+            .map(|w| (player.controller, w.clone()))
+            .collect();
+
+        economy.on_entities_updated(
+            &[player],
+            &rules(),
+            &round(1, RoundPhase::FreezeTime),
+            &pickups,
+            tick,
+            events,
+        );
+    }
+
+    /// an `item_purchase` of the player in slot 0
+    fn item_purchase(economy: &mut Economy, item: &str, tick: u32, events: &mut PendingEvents) {
+        let names = ["userid", "weapon"].map(String::from);
+        let keys = [
+            KeyT {
+                val_short: Some(0),
+                ..Default::default()
+            },
+            KeyT {
+                val_string: Some(item.to_string()),
+                ..Default::default()
+            },
+        ];
+        let keys = GameEventKeys {
+            names: &names,
+            keys: &keys,
+        };
+
+        economy.on_game_event("item_purchase", &keys, &[player(0)], tick, events);
+    }
+
+    fn purchases(economy: &Economy) -> &[Purchase] {
+        &economy.rounds()[0].purchases
+    }
+
+    fn assert_paired(economy: &Economy, events: &PendingEvents) {
+        assert_eq!(events.0.len(), 1);
+        assert_eq!(purchases(economy).len(), 1);
+
+        let purchase = &purchases(economy)[0];
+        assert_eq!(purchase.item.as_deref(), Some("weapon_ak47"));
+        assert_eq!(purchase.cost, Some(2700));
+        assert_eq!(purchase.weapon, Some(ak47().entity));
+    }
+
+    #[test]
+    fn money_before_item_purchase_before_pickup() {
+        let mut economy = Economy::default();
+        let mut events = PendingEvents::default();
+        update(&mut economy, 0, &[], 1, &mut events);
+
+        update(&mut economy, 2700, &[], 2, &mut events);
+        item_purchase(&mut economy, "weapon_ak47", 2, &mut events);
+        update(&mut economy, 2700, &[ak47()], 2, &mut events);
+
+        assert_paired(&economy, &events);
+    }
+
+    #[test]
+    fn item_purchase_before_money_and_pickup() {
+        let mut economy = Economy::default();
+        let mut events = PendingEvents::default();
+        update(&mut economy, 0, &[], 1, &mut events);
+
+        item_purchase(&mut economy, "weapon_ak47", 2, &mut events);
+        update(&mut economy, 2700, &[ak47()], 2, &mut events);
+
+        assert_paired(&economy, &events);
+    }
+
+    #[test]
+    fn money_and_pickup_before_item_purchase() {
+        let mut economy = Economy::default();
+        let mut events = PendingEvents::default();
+        update(&mut economy, 0, &[], 1, &mut events);
+
+        update(&mut economy, 2700, &[ak47()], 2, &mut events);
+        item_purchase(&mut economy, "weapon_ak47", 2, &mut events);
+
+        assert_paired(&economy, &events);
+    }
+
+    #[test]
+    fn refund_matches_the_cost() {
+        let mut economy = Economy::default();
+        let mut events = PendingEvents::default();
+        update(&mut economy, 0, &[], 1, &mut events);
+        update(&mut economy, 2700, &[], 2, &mut events);
+        update(&mut economy, 3350, &[], 3, &mut events);
+
+        update(&mut economy, 650, &[], 4, &mut events);
+
+        // two purchases and one refund
+        assert_eq!(events.0.len(), 3);
+        assert!(purchases(&economy)[0].refunded);
+        assert!(!purchases(&economy)[1].refunded);
+
+        // no purchase costs 100
+        update(&mut economy, 550, &[], 5, &mut events);
+        assert_eq!(events.0.len(), 3);
+    }
+
+    #[test]
+    fn restart_clears_the_rounds() {
+        let mut economy = Economy::default();
+        let mut events = PendingEvents::default();
+        for number in 1..=3 {
+            let round = round(number, RoundPhase::Live);
+            economy.on_entities_updated(
+                &[player(0)],
+                &rules(),
+                &round,
+                &[],
+                number as u32,
+                &mut events,
+            );
+        }
+        assert_eq!(economy.rounds().len(), 3);
+
+        let round = round(1, RoundPhase::FreezeTime);
+        economy.on_entities_updated(&[player(0)], &rules(), &round, &[], 4, &mut events);
+
+        assert_eq!(economy.rounds().len(), 1);
+        assert_eq!(economy.rounds()[0].round, 1);
+        assert!(economy.rounds()[0].pistol);
+    }
+
+    #[test]
+    fn first_round_after_the_halftime_is_a_pistol_round() {
+        let mut economy = Economy::default();
+        let mut events = PendingEvents::default();
+        let mut update = |number, game_phase, overtime_playing| {
+            let rules = CCSGameRulesProxy {
+                game_phase,
+                overtime_playing,
+                ..rules()
+            };
+            let round = round(number, RoundPhase::FreezeTime);
+            economy.on_entities_updated(&[player(0)], &rules, &round, &[], 0, &mut events);
+        };
+
+        update(12, GamePhase::PlayingFirstHalf, 0);
+        update(12, GamePhase::Halftime, 0);
+        update(13, GamePhase::PlayingSecondHalf, 0);
+        update(14, GamePhase::PlayingSecondHalf, 0);
+        update(24, GamePhase::Halftime, 1);
+        update(25, GamePhase::PlayingSecondHalf, 1);
+
+        let pistol = |number| economy.round(number).unwrap().pistol;
+        assert!(!pistol(12));
+        assert!(pistol(13));
+        assert!(!pistol(14));
+        // overtime halves start with more money
+        assert!(!pistol(25));
+        assert_eq!(
+            economy.round(13).unwrap().teams[0].buy_type,
+            BuyType::Pistol
+        );
+    }
+
+    #[test]
+    fn classify_by_average_equipment_value() {
+        assert_eq!(BuyType::classify(0), BuyType::Eco);
+        assert_eq!(BuyType::classify(ECO_EQUIPMENT_VALUE - 1), BuyType::Eco);
+        assert_eq!(BuyType::classify(ECO_EQUIPMENT_VALUE), BuyType::Force);
+        assert_eq!(
+            BuyType::classify(FULL_BUY_EQUIPMENT_VALUE - 1),
+            BuyType::Force
+        );
+        assert_eq!(
+            BuyType::classify(FULL_BUY_EQUIPMENT_VALUE),
+            BuyType::FullBuy
+        );
+        assert_eq!(BuyType::classify(i32::MAX), BuyType::FullBuy);
+    }
+}
@@ -0,0 +1,416 @@
+use crate::{
+    entity::{
+        list::{EntityHandle, EntityList},
+        serializer::vector::Vector3,
+    },
+    game_state::{
+        GameEventKeys, PendingEvents, Player,
+        classes::{CBaseCSGrenadeProjectile, CCSPlayerController},
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GrenadeType {
+    Smoke,
+    Flashbang,
+    HighExplosive,
+    Molotov,
+    Incendiary,
+    Decoy,
+    /// any other projectile, e.g. of a game mode
+    Other,
+}
+
+impl GrenadeType {
+    fn from_class_name(class_name: &str) -> Self {
+        match class_name {
+            "CSmokeGrenadeProjectile" => GrenadeType::Smoke,
+            "CFlashbangProjectile" => GrenadeType::Flashbang,
+            "CHEGrenadeProjectile" => GrenadeType::HighExplosive,
+            // incendiaries share the class, they are told apart by `weapon_fire`
+            "CMolotovProjectile" => GrenadeType::Molotov,
+            "CDecoyProjectile" => GrenadeType::Decoy,
+            _ => GrenadeType::Other,
+        }
+    }
+
+    /// whether the `*_detonate` game event names projectiles of this type
+    fn detonates_with(self, event_name: &str) -> bool {
+        match event_name {
+            "smokegrenade_detonate" => self == GrenadeType::Smoke,
+            "flashbang_detonate" => self == GrenadeType::Flashbang,
+            "hegrenade_detonate" => self == GrenadeType::HighExplosive,
+            "molotov_detonate" => matches!(self, GrenadeType::Molotov | GrenadeType::Incendiary),
+            "decoy_detonate" => self == GrenadeType::Decoy,
+            _ => false,
+        }
+    }
+
+    /// the `weapon` of `weapon_fire`
+    fn from_weapon_name(weapon: &str) -> Option<Self> {
+        Some(match weapon {
+            "weapon_smokegrenade" => GrenadeType::Smoke,
+            "weapon_flashbang" => GrenadeType::Flashbang,
+            "weapon_hegrenade" => GrenadeType::HighExplosive,
+            "weapon_molotov" => GrenadeType::Molotov,
+            "weapon_incgrenade" => GrenadeType::Incendiary,
+            "weapon_decoy" => GrenadeType::Decoy,
+            _ => return None,
+        })
+    }
+}
+
+/// a thrown grenade from the creation to the removal of its projectile
+#[derive(Clone, Debug)]
+pub struct Grenade {
+    pub entity: EntityHandle<CBaseCSGrenadeProjectile>,
+    /// invalid if neither the projectile nor `weapon_fire` named the thrower
+    pub thrower: EntityHandle<CCSPlayerController>,
+    pub grenade_type: GrenadeType,
+    pub throw_tick: u32,
+    pub throw_position: Vector3,
+    /// every position of the projectile after the throw, with the tick it was reached
+    pub trajectory: Vec<(u32, Vector3)>,
+    pub detonate_tick: Option<u32>,
+    pub detonate_position: Option<Vector3>,
+}
+
+impl Grenade {
+    /// the last known position of the projectile
+    pub fn position(&self) -> &Vector3 {
+        self.trajectory
+            .last()
+            .map_or(&self.throw_position, |(_, position)| position)
+    }
+}
+
+/// notifies when a projectile appears, the trajectory is empty
+pub struct GrenadeThrowEvent {
+    pub grenade: Grenade,
+}
+
+/// notifies when a `*_detonate` game event names the projectile,
+/// flashbangs and grenades are removed within the same tick
+pub struct GrenadeDetonateEvent {
+    pub entity: EntityHandle<CBaseCSGrenadeProjectile>,
+    pub thrower: EntityHandle<CCSPlayerController>,
+    pub grenade_type: GrenadeType,
+    pub tick: u32,
+    pub position: Vector3,
+}
+
+/// notifies when the projectile is removed with the complete trajectory,
+/// smokes are removed once the smoke fades
+pub struct GrenadeRemovedEvent {
+    pub grenade: Grenade,
+}
+
+game_state_event!(GrenadeThrowEvent, GrenadeDetonateEvent, GrenadeRemovedEvent);
+
+/// game events paired with the projectiles
+pub(crate) const GRENADE_GAME_EVENTS: [&str; 6] = [
+    "weapon_fire",
+    "smokegrenade_detonate",
+    "flashbang_detonate",
+    "hegrenade_detonate",
+    "molotov_detonate",
+    "decoy_detonate",
+];
+
+/// a `weapon_fire` of a grenade which is waiting for its projectile
+struct Throw {
+    thrower: EntityHandle<CCSPlayerController>,
+    grenade_type: GrenadeType,
+    tick: u32,
+}
+
+/// follows every `C*Projectile` entity while it exists
+#[derive(Default)]
+pub(crate) struct GrenadeTracker {
+    pub(crate) active: Vec<Grenade>,
+    throws: Vec<Throw>,
+    /// undetonated projectiles removed at `removed_tick`, their `*_detonate` may follow
+    /// within the same tick
+    removed: Vec<Grenade>,
+    removed_tick: u32,
+    /// reused to find removed projectiles
+    seen: Vec<bool>,
+}
+
+/// a throw is paired with a projectile created within this many ticks
+const MAX_THROW_DELAY: u32 = 64;
+
+impl GrenadeTracker {
+    pub(crate) fn on_entities_updated(
+        &mut self,
+        entities: &EntityList,
+        players: &[Player],
+        tick: u32,
+        events: &mut PendingEvents,
+    ) {
+        self.flush_removed(tick, events);
+        self.throws
+            .retain(|throw| tick.saturating_sub(throw.tick) <= MAX_THROW_DELAY);
+
+        self.seen.clear();
+        self.seen.resize(self.active.len(), false);
+
+        for (item, projectile) in entities.iter_entity::<CBaseCSGrenadeProjectile>() {
+            let handle = EntityHandle::from_raw(item.get_handle());
+            let position = projectile.position.world();
+
+            if let Some(idx) = self.active.iter().position(|g| g.entity == handle) {
+                self.seen[idx] = true;
+
+                let grenade = &mut self.active[idx];
+                if grenade.position() != &position {
+                    match grenade.trajectory.last_mut() {
+                        // several packets may arrive within a tick
+                        Some((last_tick, last)) if *last_tick == tick => *last = position,
+                        _ => grenade.trajectory.push((tick, position)),
+                    }
+                }
+                continue;
+            }
+
+            let mut thrower = players
+                .iter()
+                .find(|p| p.pawn.is_valid() && p.pawn.raw() == projectile.thrower.raw())
+                .map_or(EntityHandle::INVALID, |p| p.controller);
+            let mut grenade_type = GrenadeType::from_class_name(&item.class_name);
+            let mut throw_tick = tick;
+
+            let throw = self.throws.iter().position(|throw| {
+                (!thrower.is_valid() || throw.thrower == thrower)
+                    && (throw.grenade_type == grenade_type
+                        || (grenade_type == GrenadeType::Molotov
+                            && throw.grenade_type == GrenadeType::Incendiary))
+            });
+            if let Some(throw) = throw.map(|idx| self.throws.remove(idx)) {
+                thrower = throw.thrower;
+                grenade_type = throw.grenade_type;
+                throw_tick = throw.tick;
+            }
+
+            let grenade = Grenade {
+                entity: handle,
+                thrower,
+                grenade_type,
+                throw_tick,
+                throw_position: position,
+                trajectory: Vec::new(),
+                detonate_tick: None,
+                detonate_position: None,
+            };
+            events.push(GrenadeThrowEvent {
+                grenade: grenade.clone(),
+            });
+
+            self.active.push(grenade);
+            self.seen.push(true);
+        }
+
+        let mut idx = 0;
+        while idx < self.active.len() {
+            if self.seen[idx] {
+                idx += 1;
+                continue;
+            }
+
+            self.seen.remove(idx);
+            let grenade = self.active.remove(idx);
+            if grenade.detonate_tick.is_some() {
+                events.push(GrenadeRemovedEvent { grenade });
+            } else {
+                self.removed_tick = tick;
+                self.removed.push(grenade);
+            }
+        }
+    }
+
+    /// notifies the removal of the projectiles which did not detonate within their last tick
+    fn flush_removed(&mut self, tick: u32, events: &mut PendingEvents) {
+        if tick == self.removed_tick {
+            return;
+        }
+
+        for grenade in self.removed.drain(..) {
+            events.push(GrenadeRemovedEvent { grenade });
+        }
+    }
+
+    pub(crate) fn on_game_event(
+        &mut self,
+        name: &str,
+        keys: &GameEventKeys<'_>,
+        players: &[Player],
+        tick: u32,
+        events: &mut PendingEvents,
+    ) {
+        self.flush_removed(tick, events);
+
+        if name == "weapon_fire" {
+            let Some(grenade_type) = keys
+                .string("weapon")
+                .and_then(GrenadeType::from_weapon_name)
+            else {
+                return;
+            };
+            let Some(thrower) = keys.int("userid").and_then(|slot| {
+                players
+                    .iter()
+                    .find(|p| p.slot() as i64 == slot)
+                    .map(|p| p.controller)
+            }) else {
+                return;
+            };
+
+            self.throws.push(Throw {
+                thrower,
+                grenade_type,
+                tick,
+            });
+            return;
+        }
+
+        if !GRENADE_GAME_EVENTS.contains(&name) {
+            return;
+        }
+
+        let Some(index) = keys.int("entityid") else {
+            return;
+        };
+        // the event carries no serial, a reused index is told apart by the type
+        let matches = |g: &Grenade| {
+            g.entity.index() as i64 == index
+                && g.detonate_tick.is_none()
+                && g.grenade_type.detonates_with(name)
+        };
+
+        // flashbangs and grenades may be removed before the event of the same tick
+        let removed = self.removed.iter().position(matches);
+        let grenade = match removed {
+            Some(idx) => &mut self.removed[idx],
+            None => match self.active.iter_mut().find(|g| matches(g)) {
+                Some(grenade) => grenade,
+                None => return,
+            },
+        };
+
+        let position = match (keys.float("x"), keys.float("y"), keys.float("z")) {
+            (Some(x), Some(y), Some(z)) => Vector3 { x, y, z },
+            _ => grenade.position().clone(),
+        };
+
+        grenade.detonate_tick = Some(tick);
+        grenade.detonate_position = Some(position.clone());
+        events.push(GrenadeDetonateEvent {
+            entity: grenade.entity,
+            thrower: grenade.thrower,
+            grenade_type: grenade.grenade_type,
+            tick,
+            position,
+        });
+
+        if let Some(idx) = removed {
+            events.push(GrenadeRemovedEvent {
+                grenade: self.removed.remove(idx),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_event::derive::KeyT;
+
+    fn grenade(index: u32, serial: u32, grenade_type: GrenadeType) -> Grenade {
+        Grenade {
+            entity: EntityHandle::from_raw((index | serial << 14) as u64),
+            thrower: EntityHandle::INVALID,
+            grenade_type,
+            throw_tick: 0,
+            throw_position: Vector3 {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            trajectory: Vec::new(),
+            detonate_tick: None,
+            detonate_position: None,
+        }
+    }
+
+    fn detonate(
+        tracker: &mut GrenadeTracker,
+        name: &str,
+        index: u32,
+        tick: u32,
+        events: &mut PendingEvents,
+    ) {
+        let names = [String::from("entityid")];
+        let keys = [KeyT {
+            val_short: Some(index as i32),
+            ..Default::default()
+        }];
+        let keys = GameEventKeys {
+            names: &names,
+            keys: &keys,
+        };
+
+        tracker.on_game_event(name, &keys, &[], tick, events);
+    }
+
+    #[test]
+    fn detonate_after_the_removal_in_the_same_tick() {
+        let mut tracker = GrenadeTracker::default();
+        let mut events = PendingEvents::default();
+        tracker
+            .removed
+            .push(grenade(5, 1, GrenadeType::HighExplosive));
+        tracker.removed_tick = 10;
+        // a new projectile reusing the index
+        tracker.active.push(grenade(5, 2, GrenadeType::Flashbang));
+
+        detonate(&mut tracker, "hegrenade_detonate", 5, 10, &mut events);
+
+        // detonate and removal of the grenade
+        assert_eq!(events.0.len(), 2);
+        assert!(tracker.removed.is_empty());
+        assert_eq!(tracker.active[0].detonate_tick, None);
+    }
+
+    #[test]
+    fn detonate_matches_the_type_of_the_event() {
+        let mut tracker = GrenadeTracker::default();
+        let mut events = PendingEvents::default();
+        tracker.active.push(grenade(5, 1, GrenadeType::Smoke));
+
+        detonate(&mut tracker, "flashbang_detonate", 5, 10, &mut events);
+        assert_eq!(events.0.len(), 0);
+
+        detonate(&mut tracker, "smokegrenade_detonate", 5, 10, &mut events);
+        assert_eq!(events.0.len(), 1);
+        assert_eq!(tracker.active[0].detonate_tick, Some(10));
+
+        // a detonated projectile is not matched again
+        detonate(&mut tracker, "smokegrenade_detonate", 5, 11, &mut events);
+        assert_eq!(events.0.len(), 1);
+    }
+
+    #[test]
+    fn removal_is_notified_once_the_tick_ends() {
+        let mut tracker = GrenadeTracker::default();
+        let mut events = PendingEvents::default();
+        tracker.removed.push(grenade(5, 1, GrenadeType::Decoy));
+        tracker.removed_tick = 10;
+
+        detonate(&mut tracker, "hegrenade_detonate", 7, 10, &mut events);
+        assert_eq!(events.0.len(), 0);
+
+        detonate(&mut tracker, "hegrenade_detonate", 7, 11, &mut events);
+        assert_eq!(events.0.len(), 1);
+        assert!(tracker.removed.is_empty());
+    }
+}
@@ -0,0 +1,348 @@
+use crate::{
+    entity::{
+        list::{EntityHandle, EntityList},
+        serializer::vector::{Vector2, Vector3},
+    },
+    game_state::{
+        PendingEvents, Player,
+        classes::{CCSPlayerController, CInferno},
+    },
+};
+
+/// fire of a molotov or incendiary, from the first flame until the last one dies
+#[derive(Clone, Debug)]
+pub struct Inferno {
+    pub entity: EntityHandle<CInferno>,
+    /// invalid if the owner is not a known player
+    pub thrower: EntityHandle<CCSPlayerController>,
+    pub start_tick: u32,
+    /// positions of the flames which are burning
+    pub fires: Vec<Vector3>,
+    /// convex hull of the burning flames on the ground plane, counter-clockwise
+    pub hull: Vec<Vector2>,
+    pub extinguished: bool,
+}
+
+impl Inferno {
+    /// area covered by the hull
+    pub fn area(&self) -> f32 {
+        polygon_area(&self.hull)
+    }
+}
+
+/// notifies when the first flame of an inferno burns
+pub struct InfernoStartEvent {
+    pub inferno: Inferno,
+}
+
+/// notifies at most once per tick while the burning flames of an inferno change
+pub struct InfernoUpdateEvent {
+    pub entity: EntityHandle<CInferno>,
+    pub tick: u32,
+    pub fires: Vec<Vector3>,
+    pub hull: Vec<Vector2>,
+}
+
+/// notifies when no flame burns anymore or the inferno is removed
+pub struct InfernoExtinguishEvent {
+    pub entity: EntityHandle<CInferno>,
+    pub thrower: EntityHandle<CCSPlayerController>,
+    pub start_tick: u32,
+    pub tick: u32,
+}
+
+game_state_event!(
+    InfernoStartEvent,
+    InfernoUpdateEvent,
+    InfernoExtinguishEvent
+);
+
+/// diffs the flames of every `CInferno` between updates
+#[derive(Default)]
+pub(crate) struct InfernoTracker {
+    pub(crate) active: Vec<Inferno>,
+    /// reused to find removed infernos
+    seen: Vec<bool>,
+    /// reused to collect the burning flames
+    fires: Vec<Vector3>,
+}
+
+impl InfernoTracker {
+    pub(crate) fn on_entities_updated(
+        &mut self,
+        entities: &EntityList,
+        players: &[Player],
+        tick: u32,
+        events: &mut PendingEvents,
+    ) {
+        self.seen.clear();
+        self.seen.resize(self.active.len(), false);
+
+        for (item, inferno) in entities.iter_entity::<CInferno>() {
+            let handle = EntityHandle::from_raw(item.get_handle());
+
+            self.fires.clear();
+            self.fires.extend(
+                inferno
+                    .fire_positions
+                    .iter()
+                    .zip(&inferno.fire_is_burning)
+                    .take(inferno.fire_count.max(0) as usize)
+                    .filter(|(_, burning)| **burning)
+                    .map(|(position, _)| position.clone()),
+            );
+
+            let idx = match self.active.iter().position(|i| i.entity == handle) {
+                Some(idx) => idx,
+                // the entity may be created before its first flame is networked
+                None if self.fires.is_empty() => continue,
+                None => {
+                    let thrower = players
+                        .iter()
+                        .find(|p| p.pawn.is_valid() && p.pawn.raw() == inferno.owner.raw())
+                        .map_or(EntityHandle::INVALID, |p| p.controller);
+
+                    let started = Inferno {
+                        entity: handle,
+                        thrower,
+                        start_tick: tick,
+                        hull: convex_hull(&self.fires),
+                        fires: self.fires.clone(),
+                        extinguished: false,
+                    };
+                    events.push(InfernoStartEvent {
+                        inferno: started.clone(),
+                    });
+
+                    self.active.push(started);
+                    self.seen.push(true);
+                    continue;
+                }
+            };
+
+            self.seen[idx] = true;
+
+            let active = &mut self.active[idx];
+            if active.extinguished || active.fires == self.fires {
+                continue;
+            }
+
+            if self.fires.is_empty() {
+                active.extinguished = true;
+                active.fires.clear();
+                active.hull.clear();
+                events.push(InfernoExtinguishEvent {
+                    entity: active.entity,
+                    thrower: active.thrower,
+                    start_tick: active.start_tick,
+                    tick,
+                });
+                continue;
+            }
+
+            active.fires.clone_from(&self.fires);
+            active.hull = convex_hull(&active.fires);
+            events.push(InfernoUpdateEvent {
+                entity: active.entity,
+                tick,
+                fires: active.fires.clone(),
+                hull: active.hull.clone(),
+            });
+        }
+
+        let mut idx = 0;
+        while idx < self.active.len() {
+            if self.seen[idx] {
+                idx += 1;
+                continue;
+            }
+
+            self.seen.remove(idx);
+            let removed = self.active.remove(idx);
+            if !removed.extinguished {
+                events.push(InfernoExtinguishEvent {
+                    entity: removed.entity,
+                    thrower: removed.thrower,
+                    start_tick: removed.start_tick,
+                    tick,
+                });
+            }
+        }
+    }
+}
+
+/// convex hull of the points projected on the ground plane, counter-clockwise
+pub fn convex_hull(points: &[Vector3]) -> Vec<Vector2> {
+    let mut points = points
+        .iter()
+        .map(|p| Vector2 { x: p.x, y: p.y })
+        .collect::<Vec<_>>();
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+
+    if points.len() < 3 {
+        return points;
+    }
+
+    // monotone chain, the lower hull followed by the upper hull
+    let mut hull: Vec<Vector2> = Vec::with_capacity(points.len() + 1);
+    for point in &points {
+        while hull.len() >= 2 && cross(&hull[hull.len() - 2], &hull[hull.len() - 1], point) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(point.clone());
+    }
+
+    // the upper hull must not pop points of the lower hull
+    let lower_len = hull.len() + 1;
+    for point in points.iter().rev().skip(1) {
+        while hull.len() >= lower_len
+            && cross(&hull[hull.len() - 2], &hull[hull.len() - 1], point) <= 0.0
+        {
+            hull.pop();
+        }
+        hull.push(point.clone());
+    }
+
+    // the last point is the first one again
+    hull.pop();
+    hull
+}
+
+/// z of the cross product of `a - o` and `b - o`, positive for a counter-clockwise turn
+fn cross(o: &Vector2, a: &Vector2, b: &Vector2) -> f32 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+/// area of a simple polygon
+pub fn polygon_area(polygon: &[Vector2]) -> f32 {
+    if polygon.len() < 3 {
+        return 0.0;
+    }
+
+    let twice_area = polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum::<f32>();
+
+    twice_area.abs() / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(x: f32, y: f32) -> Vector3 {
+        Vector3 { x, y, z: 0.0 }
+    }
+
+    fn flat(x: f32, y: f32) -> Vector2 {
+        Vector2 { x, y }
+    }
+
+    #[test]
+    fn convex_hull_drops_inner_and_edge_points() {
+        let points = [
+            point(0.0, 0.0),
+            point(1.0, 0.0),
+            point(2.0, 0.0),
+            point(2.0, 2.0),
+            point(1.0, 1.0),
+            point(0.0, 2.0),
+            point(0.0, 1.0),
+        ];
+
+        let hull = convex_hull(&points);
+
+        assert_eq!(
+            hull,
+            [
+                flat(0.0, 0.0),
+                flat(2.0, 0.0),
+                flat(2.0, 2.0),
+                flat(0.0, 2.0)
+            ]
+        );
+        assert_eq!(polygon_area(&hull), 4.0);
+    }
+
+    #[test]
+    fn convex_hull_ignores_height() {
+        let points = [
+            Vector3 {
+                x: 0.0,
+                y: 0.0,
+                z: 10.0,
+            },
+            Vector3 {
+                x: 1.0,
+                y: 0.0,
+                z: -5.0,
+            },
+            Vector3 {
+                x: 0.0,
+                y: 1.0,
+                z: 3.0,
+            },
+        ];
+
+        assert_eq!(
+            convex_hull(&points),
+            [flat(0.0, 0.0), flat(1.0, 0.0), flat(0.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn convex_hull_of_collinear_points() {
+        let points = [
+            point(2.0, 2.0),
+            point(0.0, 0.0),
+            point(1.0, 1.0),
+            point(3.0, 3.0),
+        ];
+
+        let hull = convex_hull(&points);
+
+        assert_eq!(hull, [flat(0.0, 0.0), flat(3.0, 3.0)]);
+        assert_eq!(polygon_area(&hull), 0.0);
+    }
+
+    #[test]
+    fn convex_hull_of_duplicate_points() {
+        let points = [point(1.0, 1.0), point(1.0, 1.0), point(1.0, 1.0)];
+        assert_eq!(convex_hull(&points), [flat(1.0, 1.0)]);
+
+        let points = [
+            point(0.0, 0.0),
+            point(1.0, 0.0),
+            point(0.0, 0.0),
+            point(0.0, 1.0),
+            point(1.0, 0.0),
+        ];
+        assert_eq!(
+            convex_hull(&points),
+            [flat(0.0, 0.0), flat(1.0, 0.0), flat(0.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn convex_hull_of_no_points() {
+        assert!(convex_hull(&[]).is_empty());
+    }
+
+    #[test]
+    fn polygon_area_ignores_orientation() {
+        let counter_clockwise = [flat(0.0, 0.0), flat(4.0, 0.0), flat(0.0, 3.0)];
+        let clockwise = [flat(0.0, 0.0), flat(0.0, 3.0), flat(4.0, 0.0)];
+
+        assert_eq!(polygon_area(&counter_clockwise), 6.0);
+        assert_eq!(polygon_area(&clockwise), 6.0);
+    }
+
+    #[test]
+    fn polygon_area_of_degenerate_polygons() {
+        assert_eq!(polygon_area(&[]), 0.0);
+        assert_eq!(polygon_area(&[flat(1.0, 1.0), flat(2.0, 2.0)]), 0.0);
+    }
+}
@@ -0,0 +1,434 @@
+use crate::{
+    entity::list::{EntityHandle, EntityList},
+    game_state::{
+        PendingEvents, Player,
+        classes::{CC4, CCSPlayerController, CCSPlayerPawn, CCSWeaponBase, WeaponBase},
+    },
+};
+
+/// a weapon held by a player
+#[derive(Clone, Debug, PartialEq)]
+pub struct InventoryWeapon {
+    /// the bomb is decoded as `CC4`, resolve it with `EntityList::get_by_handle`
+    pub entity: EntityHandle<CCSWeaponBase>,
+    pub item_definition_index: u16,
+    /// e.g. `weapon_ak47`, None for unknown item definitions
+    pub name: Option<&'static str>,
+    /// -1 for weapons without a clip
+    pub clip: i32,
+    pub reserve_ammo: i32,
+}
+
+impl InventoryWeapon {
+    fn new(entity: EntityHandle<CCSWeaponBase>, weapon: &WeaponBase) -> Self {
+        Self {
+            entity,
+            item_definition_index: weapon.item_definition_index,
+            name: item_definition_name(weapon.item_definition_index),
+            clip: weapon.clip,
+            reserve_ammo: weapon.reserve_ammo.first().copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// the weapons of a player
+#[derive(Clone, Debug)]
+pub struct Inventory {
+    pub player: EntityHandle<CCSPlayerController>,
+    pub pawn: EntityHandle<CCSPlayerPawn>,
+    /// in the order of `m_hMyWeapons`
+    pub weapons: Vec<InventoryWeapon>,
+    pub active_weapon: Option<EntityHandle<CCSWeaponBase>>,
+}
+
+impl Inventory {
+    pub fn active_weapon(&self) -> Option<&InventoryWeapon> {
+        let active = self.active_weapon?;
+        self.weapons.iter().find(|w| w.entity == active)
+    }
+
+    /// looks up a weapon by its name, e.g. `weapon_c4`
+    pub fn weapon(&self, name: &str) -> Option<&InventoryWeapon> {
+        self.weapons.iter().find(|w| w.name == Some(name))
+    }
+}
+
+/// notifies when a weapon enters the inventory, including purchases and the spawn equipment
+pub struct WeaponPickupEvent {
+    pub player: EntityHandle<CCSPlayerController>,
+    pub weapon: InventoryWeapon,
+}
+
+/// notifies when a weapon leaves the inventory,
+/// including thrown grenades and the weapons of dying players
+pub struct WeaponDropEvent {
+    pub player: EntityHandle<CCSPlayerController>,
+    pub weapon: InventoryWeapon,
+}
+
+/// notifies when the active weapon changes
+pub struct WeaponSwitchEvent {
+    pub player: EntityHandle<CCSPlayerController>,
+    pub from: Option<InventoryWeapon>,
+    pub to: Option<InventoryWeapon>,
+}
+
+game_state_event!(WeaponPickupEvent, WeaponDropEvent, WeaponSwitchEvent);
+
+/// resolves the weapon handles of every pawn and diffs them between updates
+#[derive(Default)]
+pub(crate) struct InventoryTracker {
+    /// ordered like the players
+    pub(crate) inventories: Vec<Inventory>,
+    /// reused to collect the weapons of a pawn
+    weapons: Vec<InventoryWeapon>,
+    /// the pickups of the last update, read by the economy to pair purchases
+    pub(crate) pickups: Vec<(EntityHandle<CCSPlayerController>, InventoryWeapon)>,
+}
+
+impl InventoryTracker {
+    pub(crate) fn on_entities_updated(
+        &mut self,
+        entities: &EntityList,
+        players: &[Player],
+        events: &mut PendingEvents,
+    ) {
+        self.pickups.clear();
+        self.inventories
+            .retain(|inventory| players.iter().any(|p| p.controller == inventory.player));
+
+        for player in players {
+            let pawn = player.pawn.resolve(entities);
+
+            self.weapons.clear();
+            if let Some(pawn) = pawn {
+                self.weapons
+                    .extend(pawn.weapons.iter().filter_map(|handle| {
+                        resolve_weapon(entities, *handle).map(|w| InventoryWeapon::new(*handle, w))
+                    }));
+            }
+            let active_weapon = pawn
+                .map(|pawn| pawn.active_weapon)
+                .filter(|handle| self.weapons.iter().any(|w| w.entity == *handle));
+
+            let idx = match self
+                .inventories
+                .iter()
+                .position(|inventory| inventory.player == player.controller)
+            {
+                Some(idx) => idx,
+                None => {
+                    // the first observation is not a pickup, e.g. at the start of the demo
+                    self.inventories.push(Inventory {
+                        player: player.controller,
+                        pawn: player.pawn,
+                        weapons: self.weapons.clone(),
+                        active_weapon,
+                    });
+                    continue;
+                }
+            };
+
+            let inventory = &mut self.inventories[idx];
+            inventory.pawn = player.pawn;
+
+            for weapon in &inventory.weapons {
+                if !self.weapons.iter().any(|w| w.entity == weapon.entity) {
+                    events.push(WeaponDropEvent {
+                        player: player.controller,
+                        weapon: weapon.clone(),
+                    });
+                }
+            }
+            for weapon in &self.weapons {
+                if !inventory.weapons.iter().any(|w| w.entity == weapon.entity) {
+                    self.pickups.push((player.controller, weapon.clone()));
+                    events.push(WeaponPickupEvent {
+                        player: player.controller,
+                        weapon: weapon.clone(),
+                    });
+                }
+            }
+
+            if inventory.active_weapon != active_weapon {
+                let from = inventory.active_weapon().cloned();
+                let to = active_weapon
+                    .and_then(|active| self.weapons.iter().find(|w| w.entity == active))
+                    .cloned();
+
+                inventory.active_weapon = active_weapon;
+                events.push(WeaponSwitchEvent {
+                    player: player.controller,
+                    from,
+                    to,
+                });
+            }
+
+            if inventory.weapons != self.weapons {
+                inventory.weapons.clone_from(&self.weapons);
+            }
+        }
+
+        let order = |inventory: &Inventory| {
+            players
+                .iter()
+                .position(|p| p.controller == inventory.player)
+        };
+        self.inventories.sort_by_key(order);
+    }
+}
+
+/// resolves weapons of any class, the bomb included
+fn resolve_weapon(
+    entities: &EntityList,
+    handle: EntityHandle<CCSWeaponBase>,
+) -> Option<&WeaponBase> {
+    let item = handle.resolve_item(entities)?;
+
+    item.item
+        .downcast_ref::<CCSWeaponBase>()
+        .map(|w| &w.weapon)
+        .or_else(|| item.item.downcast_ref::<CC4>().map(|c4| &c4.weapon))
+}
+
+/// name of an item definition index, as used by `item_purchase` and `weapon_fire`
+pub fn item_definition_name(index: u16) -> Option<&'static str> {
+    Some(match index {
+        1 => "weapon_deagle",
+        2 => "weapon_elite",
+        3 => "weapon_fiveseven",
+        4 => "weapon_glock",
+        7 => "weapon_ak47",
+        8 => "weapon_aug",
+        9 => "weapon_awp",
+        10 => "weapon_famas",
+        11 => "weapon_g3sg1",
+        13 => "weapon_galilar",
+        14 => "weapon_m249",
+        16 => "weapon_m4a1",
+        17 => "weapon_mac10",
+        19 => "weapon_p90",
+        23 => "weapon_mp5sd",
+        24 => "weapon_ump45",
+        25 => "weapon_xm1014",
+        26 => "weapon_bizon",
+        27 => "weapon_mag7",
+        28 => "weapon_negev",
+        29 => "weapon_sawedoff",
+        30 => "weapon_tec9",
+        31 => "weapon_taser",
+        32 => "weapon_hkp2000",
+        33 => "weapon_mp7",
+        34 => "weapon_mp9",
+        35 => "weapon_nova",
+        36 => "weapon_p250",
+        38 => "weapon_scar20",
+        39 => "weapon_sg556",
+        40 => "weapon_ssg08",
+        41 => "weapon_knifegg",
+        42 => "weapon_knife",
+        43 => "weapon_flashbang",
+        44 => "weapon_hegrenade",
+        45 => "weapon_smokegrenade",
+        46 => "weapon_molotov",
+        47 => "weapon_decoy",
+        48 => "weapon_incgrenade",
+        49 => "weapon_c4",
+        57 => "weapon_healthshot",
+        59 => "weapon_knife_t",
+        60 => "weapon_m4a1_silencer",
+        61 => "weapon_usp_silencer",
+        63 => "weapon_cz75a",
+        64 => "weapon_revolver",
+        500 => "weapon_bayonet",
+        503 => "weapon_knife_css",
+        505 => "weapon_knife_flip",
+        506 => "weapon_knife_gut",
+        507 => "weapon_knife_karambit",
+        508 => "weapon_knife_m9_bayonet",
+        509 => "weapon_knife_tactical",
+        512 => "weapon_knife_falchion",
+        514 => "weapon_knife_survival_bowie",
+        515 => "weapon_knife_butterfly",
+        516 => "weapon_knife_push",
+        517 => "weapon_knife_cord",
+        518 => "weapon_knife_canis",
+        519 => "weapon_knife_ursus",
+        520 => "weapon_knife_gypsy_jackknife",
+        521 => "weapon_knife_outdoor",
+        522 => "weapon_knife_stiletto",
+        523 => "weapon_knife_widowmaker",
+        525 => "weapon_knife_skeleton",
+        526 => "weapon_knife_kukri",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        entity::{EntitySerializerCreator, list::EntityItem, schema::ClassSchema},
+        game_state::{classes::Team, damage},
+    };
+
+    const PAWN: u32 = 10;
+    const KNIFE: u32 = 20;
+    const AK47: u32 = 21;
+
+    fn handle<T>(index: u32) -> EntityHandle<T> {
+        EntityHandle::from_raw((index | 1 << 14) as u64)
+    }
+
+    fn player() -> Player {
+        Player {
+            pawn: handle(PAWN),
+            ..damage::tests::player(0, Team::Terrorist, 100)
+        }
+    }
+
+    fn insert<T: Send + Sync + 'static>(
+        entities: &mut EntityList,
+        index: u32,
+        item: T,
+        creator: EntitySerializerCreator,
+    ) {
+        let serializer = creator(Vec::new());
+        entities.insert(
+            index as usize,
+            EntityItem {
+                index,
+                serial: 1,
+                class_name: Arc::from(""),
+                item: Box::new(item),
+                serializer,
+                schema: Arc::new(ClassSchema {
+                    name: Arc::from(""),
+                    version: None,
+                    fields: Box::new([]),
+                }),
+            },
+        );
+    }
+
+    /// an entity list with the knife and the ak47 on the ground
+    fn entities() -> EntityList {
+        let mut entities = EntityList::new();
+        for (index, item_definition_index) in [(KNIFE, 42), (AK47, 7)] {
+            let mut weapon = CCSWeaponBase::default();
+            weapon.weapon.item_definition_index = item_definition_index;
+            insert(&mut entities, index, weapon, CCSWeaponBase::new_serializer);
+        }
+        entities
+    }
+
+    fn set_pawn(entities: &mut EntityList, weapons: &[u32], active_weapon: u32) {
+        let pawn = CCSPlayerPawn {
+            weapons: weapons.iter().map(|index| handle(*index)).collect(),
+            active_weapon: handle(active_weapon),
+            ..Default::default()
+        };
+        insert(entities, PAWN, pawn, CCSPlayerPawn::new_serializer);
+    }
+
+    /// a tracker which observed the player holding the knife
+    fn tracker(entities: &mut EntityList) -> InventoryTracker {
+        let mut tracker = InventoryTracker::default();
+        let mut events = PendingEvents::default();
+        set_pawn(entities, &[KNIFE], KNIFE);
+        tracker.on_entities_updated(entities, &[player()], &mut events);
+
+        assert_eq!(events.0.len(), 0);
+        tracker
+    }
+
+    #[test]
+    fn first_observation_is_no_pickup() {
+        let mut entities = entities();
+        let tracker = tracker(&mut entities);
+
+        let inventory = &tracker.inventories[0];
+        assert_eq!(inventory.weapons.len(), 1);
+        assert_eq!(
+            inventory.active_weapon().and_then(|w| w.name),
+            Some("weapon_knife")
+        );
+        assert!(tracker.pickups.is_empty());
+    }
+
+    #[test]
+    fn new_weapon_is_a_pickup() {
+        let mut entities = entities();
+        let mut tracker = tracker(&mut entities);
+        let mut events = PendingEvents::default();
+
+        set_pawn(&mut entities, &[KNIFE, AK47], KNIFE);
+        tracker.on_entities_updated(&entities, &[player()], &mut events);
+
+        assert_eq!(events.0.len(), 1);
+        assert_eq!(tracker.pickups.len(), 1);
+        assert_eq!(tracker.pickups[0].1.name, Some("weapon_ak47"));
+        assert!(tracker.inventories[0].weapon("weapon_ak47").is_some());
+    }
+
+    #[test]
+    fn missing_weapon_is_a_drop() {
+        let mut entities = entities();
+        let mut tracker = tracker(&mut entities);
+        let mut events = PendingEvents::default();
+
+        set_pawn(&mut entities, &[], KNIFE);
+        tracker.on_entities_updated(&entities, &[player()], &mut events);
+
+        // the drop and the switch away from the dropped knife
+        assert_eq!(events.0.len(), 2);
+        assert!(tracker.pickups.is_empty());
+        assert!(tracker.inventories[0].weapons.is_empty());
+        assert_eq!(tracker.inventories[0].active_weapon, None);
+    }
+
+    #[test]
+    fn active_weapon_which_is_not_held_is_none() {
+        let mut entities = entities();
+        let mut tracker = tracker(&mut entities);
+        let mut events = PendingEvents::default();
+
+        // the ak47 is networked but not in the weapons of the pawn
+        set_pawn(&mut entities, &[KNIFE], AK47);
+        tracker.on_entities_updated(&entities, &[player()], &mut events);
+
+        assert_eq!(events.0.len(), 1);
+        assert_eq!(tracker.inventories[0].active_weapon, None);
+
+        set_pawn(&mut entities, &[KNIFE, AK47], AK47);
+        tracker.on_entities_updated(&entities, &[player()], &mut events);
+
+        // the pickup and the switch to the ak47
+        assert_eq!(events.0.len(), 3);
+        assert_eq!(tracker.inventories[0].active_weapon, Some(handle(AK47)));
+    }
+
+    #[test]
+    fn removed_player_drops_the_inventory_silently() {
+        let mut entities = entities();
+        let mut tracker = tracker(&mut entities);
+        let mut events = PendingEvents::default();
+
+        tracker.on_entities_updated(&entities, &[], &mut events);
+
+        assert_eq!(events.0.len(), 0);
+        assert!(tracker.inventories.is_empty());
+    }
+
+    #[test]
+    fn item_definition_names() {
+        assert_eq!(item_definition_name(7), Some("weapon_ak47"));
+        assert_eq!(item_definition_name(49), Some("weapon_c4"));
+        assert_eq!(item_definition_name(60), Some("weapon_m4a1_silencer"));
+        assert_eq!(item_definition_name(507), Some("weapon_knife_karambit"));
+        assert_eq!(item_definition_name(0), None);
+        assert_eq!(item_definition_name(5), None);
+    }
+}
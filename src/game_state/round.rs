@@ -0,0 +1,299 @@
+use crate::game_state::{
+    GameEventKeys, PendingEvents,
+    classes::{CCSGameRulesProxy, RoundEndReason, Team},
+};
+
+/// notifies once per round when the freeze time of a new round begins
+pub struct RoundStartEvent {
+    /// one based number of the round
+    pub round: i32,
+}
+
+/// notifies once per round when the players are allowed to move
+pub struct FreezeTimeEndEvent {
+    pub round: i32,
+}
+
+/// notifies once per round when the winner is decided
+pub struct RoundEndEvent {
+    pub round: i32,
+    /// `Team::Unassigned` for draws
+    pub winner: Team,
+    pub reason: RoundEndReason,
+}
+
+/// notifies once per round after the round end delay, right before the next round starts
+pub struct RoundOfficiallyEndedEvent {
+    pub round: i32,
+}
+
+game_state_event!(
+    RoundStartEvent,
+    FreezeTimeEndEvent,
+    RoundEndEvent,
+    RoundOfficiallyEndedEvent
+);
+
+/// progress of the current round
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum RoundPhase {
+    /// warmup, or the start of the round has not been observed
+    #[default]
+    Unknown,
+    FreezeTime,
+    Live,
+    Ended,
+    OfficiallyEnded,
+}
+
+/// game events which drive the round lifecycle
+pub(crate) const ROUND_GAME_EVENTS: [&str; 5] = [
+    "round_start",
+    "round_freeze_end",
+    "round_end",
+    "round_officially_ended",
+    "begin_new_match",
+];
+
+/// derives the round lifecycle from the game rules and the round game events,
+/// whichever arrives first emits the event and the other one is ignored
+#[derive(Default)]
+pub(crate) struct RoundTracker {
+    pub(crate) round: i32,
+    pub(crate) phase: RoundPhase,
+    /// set when the match or the running round restarts, even if the round number is kept,
+    /// cleared once the trackers of the round have been reset
+    pub(crate) restarted: bool,
+    /// false until the first snapshot of the game rules is received
+    initialized: bool,
+}
+
+impl RoundTracker {
+    pub(crate) fn on_rules_changed(
+        &mut self,
+        previous: &CCSGameRulesProxy,
+        rules: &CCSGameRulesProxy,
+        events: &mut PendingEvents,
+    ) {
+        if !self.initialized {
+            // the demo may start at any point, only a running freeze time is a reliable start
+            self.initialized = true;
+            self.round = rules.total_rounds_played + 1;
+            if !rules.warmup_period && rules.freeze_period {
+                self.start(rules.total_rounds_played + 1, events);
+            }
+            return;
+        }
+
+        // warmup rounds are not counted, a restart resets the number of played rounds
+        if rules.warmup_period
+            || previous.warmup_period
+            || rules.total_rounds_played < previous.total_rounds_played
+        {
+            self.round = rules.total_rounds_played + 1;
+            self.phase = RoundPhase::Unknown;
+            self.restarted |= rules.warmup_period != previous.warmup_period
+                || rules.total_rounds_played < previous.total_rounds_played;
+
+            if !rules.warmup_period && rules.freeze_period {
+                self.start(rules.total_rounds_played + 1, events);
+            }
+            return;
+        }
+
+        if rules.total_rounds_played > previous.total_rounds_played {
+            self.end(
+                winner(rules.round_win_status.into()),
+                rules.round_win_reason,
+                events,
+            );
+        }
+
+        if rules.freeze_period && !previous.freeze_period {
+            self.start(rules.total_rounds_played + 1, events);
+        } else if !rules.freeze_period && previous.freeze_period {
+            self.end_freeze_time(events);
+        }
+    }
+
+    pub(crate) fn on_game_event(
+        &mut self,
+        name: &str,
+        keys: &GameEventKeys<'_>,
+        rules: &CCSGameRulesProxy,
+        events: &mut PendingEvents,
+    ) {
+        if rules.warmup_period {
+            return;
+        }
+
+        match name {
+            "round_start" => self.start(rules.total_rounds_played + 1, events),
+            "round_freeze_end" => self.end_freeze_time(events),
+            "round_end" => {
+                let winner = winner(keys.int("winner").unwrap_or_default());
+                let reason = keys
+                    .int("reason")
+                    .and_then(|v| RoundEndReason::try_from(v as u64).ok())
+                    .unwrap_or_default();
+
+                self.end(winner, reason, events);
+            }
+            "round_officially_ended" => self.officially_end(events),
+            // a restart during the freeze time keeps the round number and the freeze period
+            "begin_new_match" => {
+                self.phase = RoundPhase::Unknown;
+                self.restarted = true;
+                if rules.freeze_period {
+                    self.start(rules.total_rounds_played + 1, events);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, round: i32, events: &mut PendingEvents) {
+        if round == self.round && self.phase == RoundPhase::FreezeTime {
+            return;
+        }
+
+        // the running round starts over, e.g. after `mp_restartgame` in the first round
+        if round == self.round && self.phase == RoundPhase::Live {
+            self.restarted = true;
+        }
+
+        // the official end is not networked if the next round starts immediately
+        self.officially_end(events);
+
+        self.round = round;
+        self.phase = RoundPhase::FreezeTime;
+        events.push(RoundStartEvent { round });
+    }
+
+    fn end_freeze_time(&mut self, events: &mut PendingEvents) {
+        if self.phase != RoundPhase::FreezeTime {
+            return;
+        }
+
+        self.phase = RoundPhase::Live;
+        events.push(FreezeTimeEndEvent { round: self.round });
+    }
+
+    fn end(&mut self, winner: Team, reason: RoundEndReason, events: &mut PendingEvents) {
+        if !matches!(self.phase, RoundPhase::FreezeTime | RoundPhase::Live) {
+            return;
+        }
+
+        self.phase = RoundPhase::Ended;
+        events.push(RoundEndEvent {
+            round: self.round,
+            winner,
+            reason,
+        });
+    }
+
+    fn officially_end(&mut self, events: &mut PendingEvents) {
+        if self.phase != RoundPhase::Ended {
+            return;
+        }
+
+        self.phase = RoundPhase::OfficiallyEnded;
+        events.push(RoundOfficiallyEndedEvent { round: self.round });
+    }
+}
+
+/// the winning team of `m_iRoundWinStatus` and the `winner` of `round_end`,
+/// 1 is a draw rather than a spectator win
+fn winner(value: i64) -> Team {
+    match value {
+        2 => Team::Terrorist,
+        3 => Team::CounterTerrorist,
+        _ => Team::Unassigned,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(total_rounds_played: i32, freeze_period: bool) -> CCSGameRulesProxy {
+        CCSGameRulesProxy {
+            total_rounds_played,
+            freeze_period,
+            ..Default::default()
+        }
+    }
+
+    /// a tracker in the live phase of the first round
+    fn live_tracker() -> RoundTracker {
+        let mut tracker = RoundTracker::default();
+        let mut events = PendingEvents::default();
+        tracker.on_rules_changed(&rules(0, true), &rules(0, true), &mut events);
+        tracker.on_rules_changed(&rules(0, true), &rules(0, false), &mut events);
+
+        assert_eq!(tracker.phase, RoundPhase::Live);
+        assert_eq!(events.0.len(), 2);
+        tracker
+    }
+
+    #[test]
+    fn restart_in_the_first_round_starts_it_again() {
+        let mut tracker = live_tracker();
+        let mut events = PendingEvents::default();
+
+        tracker.on_rules_changed(&rules(0, false), &rules(0, true), &mut events);
+
+        assert_eq!(tracker.round, 1);
+        assert_eq!(tracker.phase, RoundPhase::FreezeTime);
+        assert!(tracker.restarted);
+        assert_eq!(events.0.len(), 1);
+    }
+
+    #[test]
+    fn round_start_while_live_starts_the_round_again() {
+        let mut tracker = live_tracker();
+        let mut events = PendingEvents::default();
+        let keys = GameEventKeys {
+            names: &[],
+            keys: &[],
+        };
+
+        tracker.on_game_event("round_start", &keys, &rules(0, false), &mut events);
+
+        assert_eq!(tracker.phase, RoundPhase::FreezeTime);
+        assert!(tracker.restarted);
+        assert_eq!(events.0.len(), 1);
+    }
+
+    #[test]
+    fn begin_new_match_during_the_freeze_time_starts_the_round_again() {
+        let mut tracker = RoundTracker::default();
+        let mut events = PendingEvents::default();
+        let keys = GameEventKeys {
+            names: &[],
+            keys: &[],
+        };
+        tracker.on_rules_changed(&rules(0, true), &rules(0, true), &mut events);
+
+        tracker.on_game_event("begin_new_match", &keys, &rules(0, true), &mut events);
+
+        assert_eq!(tracker.phase, RoundPhase::FreezeTime);
+        assert!(tracker.restarted);
+        assert_eq!(events.0.len(), 2);
+    }
+
+    #[test]
+    fn freeze_time_of_the_next_round_is_no_restart() {
+        let mut tracker = live_tracker();
+        let mut events = PendingEvents::default();
+
+        tracker.on_rules_changed(&rules(0, false), &rules(1, false), &mut events);
+        tracker.on_rules_changed(&rules(1, false), &rules(1, true), &mut events);
+
+        assert_eq!(tracker.round, 2);
+        assert_eq!(tracker.phase, RoundPhase::FreezeTime);
+        assert!(!tracker.restarted);
+        // round end, official end and round start
+        assert_eq!(events.0.len(), 3);
+    }
+}
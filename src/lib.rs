@@ -1,7 +1,10 @@
 pub mod bit;
 pub mod entity;
+pub mod error;
 pub mod event;
 pub mod game_event;
+pub mod game_state;
+mod seek;
 pub mod string_table;
 
 pub mod protobuf {
@@ -18,13 +21,16 @@ use bitstream_io::{BitRead, BitReader};
 use bytes::{Bytes, BytesMut};
 
 use crate::bit::BitReaderExt;
-use crate::entity::EntitySerializerCreator;
 use crate::entity::fieldpath::FieldPathFixed;
 use crate::entity::list::EntityList;
-use crate::entity::serializer::EntityClassSerializer;
-use crate::event::{DemoEndEvent, DemoStartEvent, Event, EventManager, TickEvent};
+use crate::entity::serializer::BindingError;
+use crate::entity::{ClassMatcher, ClassSerializer, EntitySerializerCreator};
+use crate::error::ParserError;
+use crate::event::{DemoEndEvent, DemoEvents, DemoStartEvent, Event, EventManager, TickEvent};
 use crate::game_event::derive::{GameEventSerializer, GameEventSerializerFactory};
+use crate::game_state::GameState;
 use crate::protobuf::{EBaseGameEvents, EDemoCommands, SvcMessages};
+use crate::seek::FullPacketIndex;
 use crate::string_table::{BaselineStringTableParser, StringTable};
 
 // 256 KiB
@@ -38,14 +44,21 @@ pub struct CsDemoParser<T: std::io::BufRead + Send + Sync> {
     #[cfg(feature = "handle_packet")]
     packet_handler: HashMap<
         u64,
-        Box<dyn Fn(Bytes, &mut EventManager, &CsDemoParserState) -> Result<(), std::io::Error>>,
+        Box<dyn Fn(Bytes, &mut EventManager, &CsDemoParserState) -> Result<(), ParserError>>,
     >,
     pub state: CsDemoParserState,
 
-    class_info: HashMap<u32, String>,
+    class_info: HashMap<u32, Arc<str>>,
     class_id_size: u32,
     entity_serializer_creators: HashMap<&'static str, EntitySerializerCreator>,
-    entity_serializers: HashMap<String, (Arc<dyn EntityClassSerializer>, bool)>,
+    entity_serializer_matchers: Vec<(ClassMatcher, EntitySerializerCreator)>,
+    /// used for classes without a registered creator instead of `UnknownEntitySerializer`
+    default_entity_serializer_creator: Option<EntitySerializerCreator>,
+    entity_serializers: HashMap<String, ClassSerializer>,
+    track_previous_entity_values: bool,
+    /// class name and error of every binding which doesn't match the send tables
+    binding_errors: Vec<(String, BindingError)>,
+    strict_entity_bindings: bool,
 
     game_event_serializers: HashMap<&'static str, GameEventSerializerFactory>,
     game_event_list: HashMap<i32, Box<dyn GameEventSerializer>>,
@@ -53,6 +66,14 @@ pub struct CsDemoParser<T: std::io::BufRead + Send + Sync> {
     string_tables: Vec<String>,
     instance_baseline: Option<StringTable<BaselineStringTableParser, Box<dyn Any + Send + Sync>>>,
 
+    /// offset of the DemFileInfo frame, zero if the demo was not finalized
+    file_info_offset: u32,
+    /// set once the DemSyncTick frame is reached, all signon data is available after that
+    signon_finished: bool,
+    full_packet_index: FullPacketIndex,
+    /// header of a frame which has been read ahead but not handled yet
+    pending_frame_header: Option<(i32, u32, usize)>,
+
     // for caching
     field_path_cache: Vec<FieldPathFixed>,
     buffer: BytesMut,
@@ -65,6 +86,7 @@ pub struct CsDemoParserState {
     pub network_protocol: i32,
 
     pub entities: EntityList,
+    game_state: Option<GameState>,
     user_info: Option<StringTable<BaselineStringTableParser, protobuf::CMsgPlayerInfo>>,
 }
 
@@ -80,7 +102,7 @@ impl CsDemoParserState {
 }
 
 impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
-    pub fn new(reader: T) -> Result<CsDemoParser<T>, std::io::Error> {
+    pub fn new(reader: T) -> Result<CsDemoParser<T>, ParserError> {
         Self::new_pre_registered(reader, HashMap::default(), HashMap::default())
     }
 
@@ -88,7 +110,7 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
         mut reader: T,
         game_event_serializers: HashMap<&'static str, GameEventSerializerFactory>,
         entity_serializer_creators: HashMap<&'static str, EntitySerializerCreator>,
-    ) -> Result<CsDemoParser<T>, std::io::Error> {
+    ) -> Result<CsDemoParser<T>, ParserError> {
         /* struct protodemoheader_t
         {
             char demofilestamp[ 8 ]; // PROTODEMO_HEADER_ID
@@ -96,14 +118,15 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
         }; */
         // header + offset + 4 bytes padding
         let mut magic = [0u8; 16];
-        reader.read_exact(&mut magic)?;
+        reader
+            .read_exact(&mut magic)
+            .map_err(ParserError::frame_read)?;
         if &magic[0..8] != b"PBDEMS2\0" {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "Invalid CS2 demo magic",
-            ));
+            return Err(ParserError::invalid_data("Invalid CS2 demo magic"));
         };
 
+        let file_info_offset = u32::from_le_bytes([magic[8], magic[9], magic[10], magic[11]]);
+
         Ok(CsDemoParser {
             reader,
             event_manager: EventManager::new(),
@@ -116,16 +139,26 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
                 network_protocol: 0,
                 // most demos seems doesn't exceed 0x400 entities
                 entities: EntityList::new(),
+                game_state: None,
                 user_info: None,
             },
             class_info: HashMap::new(),
             class_id_size: 0,
             entity_serializer_creators,
+            entity_serializer_matchers: Vec::new(),
+            default_entity_serializer_creator: None,
             entity_serializers: HashMap::new(),
+            track_previous_entity_values: false,
+            binding_errors: Vec::new(),
+            strict_entity_bindings: false,
             game_event_serializers,
             game_event_list: HashMap::new(),
             string_tables: Vec::with_capacity(16),
             instance_baseline: None,
+            file_info_offset,
+            signon_finished: false,
+            full_packet_index: FullPacketIndex::new(),
+            pending_frame_header: None,
             field_path_cache: Vec::with_capacity(256),
             buffer: BytesMut::with_capacity(BUFFER_SIZE),
         })
@@ -144,12 +177,7 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
             message_type,
             Box::new(
                 |buf: Bytes, event_manager: &mut EventManager, state: &CsDemoParserState| {
-                    let msg = M::decode(buf).map_err(|err| {
-                        std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            format!("Failed to decode message: {err:?}"),
-                        )
-                    })?;
+                    let msg = M::decode(buf)?;
 
                     event_manager.notify_listeners(crate::event::PacketEvent { packet: msg }, state)
                 },
@@ -173,10 +201,16 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
         self.register_packet_handler_internal::<M>((message_type as u64) | (1 << 32));
     }
 
-    pub fn notify_listeners<E: Event>(&mut self, event: E) -> Result<(), std::io::Error> {
+    pub fn notify_listeners<E: Event>(&mut self, event: E) -> Result<(), ParserError> {
         self.event_manager.notify_listeners(event, &self.state)
     }
 
+    /// pull-based alternative to listeners,
+    /// yields built-in events and registered game events while reading frames
+    pub fn events(&mut self) -> DemoEvents<'_, T> {
+        DemoEvents::new(self)
+    }
+
     /// checks if the parser is fresh, i.e. has not parsed any frames yet
     /// only fresh parsers can register listeners
     pub fn is_fresh(&self) -> bool {
@@ -203,16 +237,13 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
         }
     }
 
-    fn snap_decompress_bytes(&mut self, input: &[u8]) -> Result<Bytes, std::io::Error> {
-        let mut buf = self.alloc_bytes(snap::raw::decompress_len(input)?);
+    fn snap_decompress_bytes(&mut self, input: &[u8]) -> Result<Bytes, ParserError> {
+        let len = snap::raw::decompress_len(input)
+            .map_err(|err| ParserError::decompression(format!("{err:?}")))?;
+        let mut buf = self.alloc_bytes(len);
         let n = snap::raw::Decoder::new()
             .decompress(input, &mut buf)
-            .map_err(|err| {
-                std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    format!("Failed to decompress: {err:?}"),
-                )
-            })?;
+            .map_err(|err| ParserError::decompression(format!("{err:?}")))?;
         buf.truncate(n);
         Ok(buf.freeze())
     }
@@ -222,33 +253,22 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
         &mut self,
         buf: Bytes,
         is_compressed: bool,
-    ) -> Result<M, std::io::Error> {
+    ) -> Result<M, ParserError> {
         let buf = if is_compressed {
             self.snap_decompress_bytes(&buf)?
         } else {
             buf
         };
 
-        let msg = M::decode(buf).map_err(|err| {
-            std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!("Failed to decode message: {err:?}"),
-            )
-        })?;
-
-        Ok(msg)
+        Ok(M::decode(buf)?)
     }
 
     #[cold]
-    fn handle_server_info(
-        &mut self,
-        msg: protobuf::CsvcMsgServerInfo,
-    ) -> Result<(), std::io::Error> {
+    fn handle_server_info(&mut self, msg: protobuf::CsvcMsgServerInfo) -> Result<(), ParserError> {
         if let Some(tick_interval) = msg.tick_interval {
             self.state.tick_interval = tick_interval;
         } else {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
+            return Err(ParserError::invalid_data(
                 "Missing tick interval in server info",
             ));
         }
@@ -256,8 +276,7 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
         if let Some(max_classes) = msg.max_classes {
             self.class_id_size = ((max_classes as f64).log2() as u32) + 1;
         } else {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
+            return Err(ParserError::invalid_data(
                 "Missing max classes in server info",
             ));
         }
@@ -271,10 +290,16 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
         data: &Bytes,
         r: &mut BitReader<Cursor<&Bytes>, bitstream_io::LittleEndian>,
         size: usize,
-    ) -> Result<Bytes, std::io::Error> {
+    ) -> Result<Bytes, ParserError> {
         Ok(if r.byte_aligned() {
             // perform zero-copy if possible
             let pos = r.position_in_bits()? as usize >> 3;
+            if pos + size > data.len() {
+                return Err(ParserError::invalid_data(
+                    "Message exceeds the size of the packet",
+                ));
+            }
+
             let buf = data.slice(pos..pos + size);
             r.seek_bits(std::io::SeekFrom::Current((size as i64) << 3))?;
             buf
@@ -285,7 +310,7 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
         })
     }
 
-    fn handle_demo_packet(&mut self, msg: protobuf::CDemoPacket) -> Result<(), std::io::Error> {
+    fn handle_demo_packet(&mut self, msg: protobuf::CDemoPacket) -> Result<(), ParserError> {
         let Some(data) = msg.data else {
             return Ok(());
         };
@@ -297,12 +322,17 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
             let message_type = r.read_ubit_int()?;
             let size = r.read_varint_u64()? as usize;
 
+            let with_context = move |err: ParserError| err.with_message_type(message_type);
+
             #[cfg(feature = "handle_packet")]
             let buf = {
-                let buf = self.read_slice_from_demo_packet(&data, &mut r, size)?;
+                let buf = self
+                    .read_slice_from_demo_packet(&data, &mut r, size)
+                    .map_err(with_context)?;
 
                 if let Some(handler) = self.packet_handler.get(&(message_type as u64)) {
-                    handler(buf.clone(), &mut self.event_manager, &self.state)?;
+                    handler(buf.clone(), &mut self.event_manager, &self.state)
+                        .map_err(with_context)?;
                 }
 
                 buf
@@ -313,11 +343,13 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
                     $(
                         if message_type == $mt as u32 {
                             #[cfg(not(feature = "handle_packet"))]
-                            let buf = self.read_slice_from_demo_packet(&data, &mut r, size)?;
+                            let buf = self
+                                .read_slice_from_demo_packet(&data, &mut r, size)
+                                .map_err(with_context)?;
 
-                            let msg = self.parse_demo_message(buf, false)?;
+                            let msg = self.parse_demo_message(buf, false).map_err(with_context)?;
 
-                            self.$handler(msg)?;
+                            self.$handler(msg).map_err(with_context)?;
 
                             continue;
                         }
@@ -353,38 +385,38 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
         Ok(())
     }
 
-    // fn handle_demo_full_packet(
-    //     &mut self,
-    //     msg: protobuf::CDemoFullPacket,
-    // ) -> Result<(), std::io::Error> {
-    //     if let Some(string_tables) = msg.string_table {
-    //         self.handle_demo_string_tables(string_tables)?;
-    //     }
+    /// full packets are snapshots of the string tables and all entities,
+    /// they are redundant while parsing sequentially and only used for seeking
+    fn handle_demo_full_packet(
+        &mut self,
+        msg: protobuf::CDemoFullPacket,
+    ) -> Result<(), ParserError> {
+        if let Some(string_tables) = msg.string_table {
+            self.handle_demo_string_tables(string_tables)?;
+        }
 
-    //     if let Some(packet) = msg.packet {
-    //         self.handle_demo_packet(packet)?;
-    //     }
+        if let Some(packet) = msg.packet {
+            self.handle_demo_packet(packet)?;
+        }
 
-    //     Ok(())
-    // }
+        Ok(())
+    }
 
     #[cold]
     fn handle_demo_file_header(
         &mut self,
         msg: protobuf::CDemoFileHeader,
-    ) -> Result<(), std::io::Error> {
+    ) -> Result<(), ParserError> {
         if let Some(map_name) = msg.map_name {
             self.state.map_name = map_name;
         } else {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
+            return Err(ParserError::invalid_data(
                 "Missing map name in demo file header",
             ));
         }
 
         let Some(network_protocol) = msg.network_protocol else {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
+            return Err(ParserError::invalid_data(
                 "Missing network protocol in demo file header",
             ));
         };
@@ -429,18 +461,36 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
         }
     }
 
-    pub fn read_frame(&mut self) -> Result<bool, std::io::Error> {
+    /// reads the command, tick and payload size of the next frame
+    fn read_frame_header(&mut self) -> Result<(i32, u32, usize), std::io::Error> {
         let cmd = self.read_varint()? as i32;
         let tick = self.read_varint()? as u32;
         let size = self.read_varint()? as usize;
 
+        Ok((cmd, tick, size))
+    }
+
+    pub fn read_frame(&mut self) -> Result<bool, ParserError> {
+        let (cmd, tick, size) = match self.pending_frame_header.take() {
+            Some(header) => header,
+            None => self.read_frame_header().map_err(ParserError::frame_read)?,
+        };
+
+        let command = cmd & !(EDemoCommands::DemIsCompressed as i32);
+        self.handle_frame(cmd, tick, size)
+            .map_err(|err| err.with_frame(tick, command))
+    }
+
+    fn handle_frame(&mut self, cmd: i32, tick: u32, size: usize) -> Result<bool, ParserError> {
         // reclaim the buffer
         if !self.buffer.try_reclaim(BUFFER_SIZE) {
             warn!("Failed to reclaim buffer, performance may degrade");
         }
 
         let mut buf = self.alloc_bytes(size);
-        self.reader.read_exact(&mut buf)?;
+        self.reader
+            .read_exact(&mut buf)
+            .map_err(ParserError::frame_read)?;
         let buf = buf.freeze();
 
         if tick != self.state.tick {
@@ -456,6 +506,8 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
         if cmd == EDemoCommands::DemStop as i32 {
             self.notify_listeners(DemoEndEvent)?;
             return Ok(false);
+        } else if cmd == EDemoCommands::DemSyncTick as i32 {
+            self.signon_finished = true;
         }
 
         #[cfg(feature = "handle_packet")]
@@ -496,7 +548,7 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
         handle_command!(
             (EDemoCommands::DemPacket, handle_demo_packet),
             (EDemoCommands::DemSignonPacket, handle_demo_packet),
-            // (EDemoCommands::DemFullPacket, handle_demo_full_packet),
+            // full packets are skipped here, see `seek_to_tick`
             (EDemoCommands::DemFileHeader, handle_demo_file_header),
             (EDemoCommands::DemSendTables, handle_demo_send_tables),
             (EDemoCommands::DemClassInfo, handle_demo_class_info),
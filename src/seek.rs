@@ -0,0 +1,260 @@
+use std::io::{BufRead, Seek, SeekFrom};
+
+use log::warn;
+
+use crate::{
+    BUFFER_SIZE, CsDemoParser, entity::list::EntityList, error::ParserError,
+    event::EntityDeletedEvent, protobuf, protobuf::EDemoCommands,
+};
+
+/// offset of the first frame, right after the demo header
+const FIRST_FRAME_OFFSET: u64 = 16;
+
+/// frame offsets of every DemFullPacket seen in the demo,
+/// the demo is scanned lazily as far as a seek requires
+pub(crate) struct FullPacketIndex {
+    /// tick and frame offset of full packets in ascending order
+    keyframes: Vec<(u32, u64)>,
+    /// offset of the next frame to scan
+    scan_offset: u64,
+    /// set once the scan reached the end of the demo
+    complete: bool,
+}
+
+impl FullPacketIndex {
+    pub(crate) fn new() -> Self {
+        Self {
+            keyframes: Vec::new(),
+            // full packets only follow the signon frames, but the reader may be anywhere
+            // at the first seek, so the scan starts at the first frame of the demo
+            scan_offset: FIRST_FRAME_OFFSET,
+            complete: false,
+        }
+    }
+
+    fn covers(&self, tick: u32) -> bool {
+        self.complete || self.keyframes.last().is_some_and(|(t, _)| *t > tick)
+    }
+
+    fn find(&self, tick: u32) -> Option<(u32, u64)> {
+        self.keyframes
+            .iter()
+            .rev()
+            .find(|(t, _)| *t <= tick)
+            .copied()
+    }
+}
+
+impl<T: BufRead + Seek + Send + Sync> CsDemoParser<T> {
+    /// reads the DemFileInfo frame at the end of the demo
+    /// using the offset from the demo header
+    ///
+    /// this does not affect the parsing progress
+    /// and is useful to get the playback time and ticks without a full parse
+    pub fn read_file_info(&mut self) -> Result<protobuf::CDemoFileInfo, ParserError> {
+        if self.file_info_offset == 0 {
+            return Err(ParserError::invalid_data(
+                "Missing file info offset in demo header",
+            ));
+        }
+
+        let position = self.reader.stream_position()?;
+        let frame = self.read_file_info_frame();
+        self.reader.seek(SeekFrom::Start(position))?;
+        let (buf, is_compressed) = frame?;
+
+        self.parse_demo_message(buf.into(), is_compressed)
+    }
+
+    /// reads the payload of the DemFileInfo frame, the reader is left anywhere
+    fn read_file_info_frame(&mut self) -> Result<(Vec<u8>, bool), ParserError> {
+        let len = self.reader.seek(SeekFrom::End(0))?;
+        self.reader
+            .seek(SeekFrom::Start(self.file_info_offset as u64))?;
+
+        let (cmd, _, size) = self.read_frame_header().map_err(ParserError::frame_read)?;
+
+        let is_compressed = cmd & EDemoCommands::DemIsCompressed as i32 != 0;
+        let cmd = cmd & !(EDemoCommands::DemIsCompressed as i32);
+        if cmd != EDemoCommands::DemFileInfo as i32 {
+            return Err(ParserError::invalid_data(format!(
+                "Unexpected command at file info offset: {cmd}"
+            )));
+        }
+
+        // a corrupted size must not allocate more than the demo holds
+        let remaining = len.saturating_sub(self.reader.stream_position()?);
+        if size as u64 > remaining {
+            return Err(ParserError::invalid_data(
+                "File info exceeds the size of the demo",
+            ));
+        }
+
+        let mut buf = vec![0u8; size];
+        self.reader
+            .read_exact(&mut buf)
+            .map_err(ParserError::frame_read)?;
+
+        Ok((buf, is_compressed))
+    }
+
+    /// seeks to the given tick by restoring the nearest preceding full packet
+    /// and replaying frames from there
+    ///
+    /// listeners are notified for every replayed frame as usual,
+    /// but not for the frames that were skipped.
+    /// restoring a full packet notifies `EntityDeletedEvent` for every entity it replaces.
+    /// the next call to `read_frame` handles the first frame after the tick.
+    /// returns false if the demo ended before the tick was reached
+    pub fn seek_to_tick(&mut self, tick: u32) -> Result<bool, ParserError> {
+        // send tables, class info and string table definitions
+        // are only sent during signon and not repeated in full packets
+        while !self.signon_finished {
+            if !self.read_frame()? {
+                return Ok(false);
+            }
+        }
+
+        if !self.full_packet_index.covers(tick) {
+            let position = self.reader.stream_position()?;
+            self.index_full_packets(tick)?;
+            self.reader.seek(SeekFrom::Start(position))?;
+        }
+
+        match self.full_packet_index.find(tick) {
+            // replaying from the current position is cheaper
+            // unless there is a full packet in between
+            Some((keyframe_tick, offset))
+                if tick < self.state.tick || keyframe_tick > self.state.tick =>
+            {
+                self.restore_full_packet(offset)?;
+            }
+            None if tick < self.state.tick => {
+                return Err(ParserError::InvalidInput(format!(
+                    "No full packet found before tick {tick}"
+                )));
+            }
+            _ => {}
+        }
+
+        loop {
+            let header = match self.pending_frame_header.take() {
+                Some(header) => header,
+                None => self.read_frame_header().map_err(ParserError::frame_read)?,
+            };
+
+            // keep the frame for the next `read_frame` call
+            if header.1 > tick {
+                self.pending_frame_header = Some(header);
+                return Ok(true);
+            }
+
+            self.pending_frame_header = Some(header);
+            if !self.read_frame()? {
+                return Ok(false);
+            }
+        }
+    }
+
+    /// scans frame headers from where the previous scan stopped until a full packet
+    /// after `tick` is found, the reader is left at an arbitrary position
+    fn index_full_packets(&mut self, tick: u32) -> Result<(), ParserError> {
+        let mut offset = self.full_packet_index.scan_offset;
+        self.reader.seek(SeekFrom::Start(offset))?;
+
+        while !self.full_packet_index.covers(tick) {
+            let (cmd, frame_tick, size) = match self.read_frame_header() {
+                Ok(header) => header,
+                Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    self.full_packet_index.complete = true;
+                    break;
+                }
+                Err(err) => return Err(err.into()),
+            };
+
+            let cmd = cmd & !(EDemoCommands::DemIsCompressed as i32);
+            if cmd == EDemoCommands::DemFullPacket as i32 {
+                self.full_packet_index.keyframes.push((frame_tick, offset));
+            } else if cmd == EDemoCommands::DemStop as i32 {
+                self.full_packet_index.complete = true;
+            }
+
+            offset = self.reader.seek(SeekFrom::Current(size as i64))?;
+        }
+
+        self.full_packet_index.scan_offset = offset;
+
+        Ok(())
+    }
+
+    /// drops all entities and string table entries,
+    /// then rebuilds them from the full packet at the given offset
+    ///
+    /// the dropped entities are notified as deleted, so listeners don't keep stale entities
+    fn restore_full_packet(&mut self, offset: u64) -> Result<(), ParserError> {
+        self.pending_frame_header = None;
+        self.reader.seek(SeekFrom::Start(offset))?;
+
+        let (cmd, tick, size) = self.read_frame_header().map_err(ParserError::frame_read)?;
+
+        if !self.buffer.try_reclaim(BUFFER_SIZE) {
+            warn!("Failed to reclaim buffer, performance may degrade");
+        }
+
+        let mut buf = self.alloc_bytes(size);
+        self.reader
+            .read_exact(&mut buf)
+            .map_err(ParserError::frame_read)?;
+
+        let is_compressed = cmd & EDemoCommands::DemIsCompressed as i32 != 0;
+        let msg: protobuf::CDemoFullPacket =
+            self.parse_demo_message(buf.freeze(), is_compressed)?;
+
+        let mut entities = std::mem::replace(&mut self.state.entities, EntityList::new());
+        if self.event_manager.is_subscribed::<EntityDeletedEvent>() {
+            let indices = entities.iter().map(|e| e.index).collect::<Vec<_>>();
+            for idx in indices {
+                if let Some(entity) = entities.delete(idx as usize) {
+                    self.notify_entity_deleted(entity)?;
+                }
+            }
+        }
+
+        if let Some(instance_baseline) = self.instance_baseline.as_mut() {
+            instance_baseline.clear();
+        }
+        if let Some(user_info) = self.state.user_info.as_mut() {
+            user_info.clear();
+        }
+        if let Some(game_state) = self.state.game_state.as_mut() {
+            game_state.reset();
+        }
+
+        self.state.tick = tick;
+        self.handle_demo_full_packet(msg)
+            .map_err(|err| err.with_frame(tick, EDemoCommands::DemFullPacket as i32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn read_file_info_rejects_a_size_beyond_the_demo() {
+        let mut demo = b"PBDEMS2\0".to_vec();
+        demo.extend_from_slice(&(FIRST_FRAME_OFFSET as u32).to_le_bytes());
+        demo.resize(FIRST_FRAME_OFFSET as usize, 0);
+        // DemFileInfo at tick 0 with a size of 2^28
+        demo.extend_from_slice(&[EDemoCommands::DemFileInfo as u8, 0]);
+        demo.extend_from_slice(&[0x80, 0x80, 0x80, 0x80, 0x01]);
+
+        let mut parser = CsDemoParser::new(Cursor::new(demo)).unwrap();
+
+        let err = parser.read_file_info().unwrap_err();
+        assert!(err.to_string().contains("exceeds the size"), "{err}");
+        assert_eq!(parser.reader.stream_position().unwrap(), FIRST_FRAME_OFFSET);
+    }
+}
@@ -6,14 +6,14 @@ use foldhash::{HashMap, HashMapExt};
 use log::error;
 use prost::Message;
 
-use crate::{CsDemoParser, bit::BitReaderExt, protobuf};
+use crate::{CsDemoParser, bit::BitReaderExt, error::ParserError, protobuf};
 
 pub const STRING_TABLE_INSTANCE_BASELINE: &str = "instancebaseline";
 pub const STRING_TABLE_USER_INFO: &str = "userinfo";
 
 type StringTableMap = HashMap<String, (i32, Option<Box<[u8]>>)>;
 pub type StringTableCacheFunction<T> =
-    Box<dyn Fn(&str, Option<&[u8]>) -> Result<StringTableCache<T>, std::io::Error> + Send + Sync>;
+    Box<dyn Fn(&str, Option<&[u8]>) -> Result<StringTableCache<T>, ParserError> + Send + Sync>;
 
 pub enum StringTableCache<T: Send + Sync> {
     Parsed(T),
@@ -71,16 +71,22 @@ where
     pub fn purge_cache(&mut self) {
         self.cache.clear();
     }
+
+    /// removes all entries along with their cache
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.cache.clear();
+    }
 }
 
 pub trait StringTableUpdatable {
-    fn update(&mut self, entries: i32, data: &[u8]) -> Result<(), std::io::Error>;
+    fn update(&mut self, entries: i32, data: &[u8]) -> Result<(), ParserError>;
     fn insert(
         &mut self,
         key: String,
         index: i32,
         value: Option<Box<[u8]>>,
-    ) -> Result<(), std::io::Error>;
+    ) -> Result<(), ParserError>;
 }
 
 impl<P, T> StringTableUpdatable for StringTable<P, T>
@@ -88,7 +94,7 @@ where
     P: StringTableParser + Send + Sync,
     T: Send + Sync,
 {
-    fn update(&mut self, entries: i32, data: &[u8]) -> Result<(), std::io::Error> {
+    fn update(&mut self, entries: i32, data: &[u8]) -> Result<(), ParserError> {
         let new_keys = self.parser.update(&mut self.map, entries, data)?;
 
         if let Some(cache_function) = &self.cache_function {
@@ -119,7 +125,7 @@ where
         key: String,
         index: i32,
         value: Option<Box<[u8]>>,
-    ) -> Result<(), std::io::Error> {
+    ) -> Result<(), ParserError> {
         if let Some(cache_function) = &self.cache_function {
             let cache_result = cache_function(&key, value.as_deref())?;
 
@@ -149,7 +155,7 @@ pub trait StringTableParser {
         map: &mut StringTableMap,
         entries: i32,
         data: &[u8],
-    ) -> Result<Vec<String>, std::io::Error>;
+    ) -> Result<Vec<String>, ParserError>;
 }
 
 const STRING_TABLE_PARSE_MAX_CACHE_SIZE: usize = 1 << 5;
@@ -167,7 +173,7 @@ impl StringTableParser for BaselineStringTableParser {
         map: &mut StringTableMap,
         entries: i32,
         data: &[u8],
-    ) -> Result<Vec<String>, std::io::Error> {
+    ) -> Result<Vec<String>, ParserError> {
         let mut r = BitReader::endian(Cursor::new(data), bitstream_io::LittleEndian);
 
         let mut idx: i32 = 0;
@@ -245,10 +251,9 @@ impl StringTableParser for BaselineStringTableParser {
                     snap::raw::Decoder::new()
                         .decompress_vec(&buf)
                         .map_err(|err| {
-                            std::io::Error::new(
-                                std::io::ErrorKind::InvalidData,
-                                format!("Failed to decompress string table: {err:?}"),
-                            )
+                            ParserError::decompression(format!(
+                                "Failed to decompress string table: {err:?}"
+                            ))
                         })?
                 } else {
                     buf
@@ -302,7 +307,7 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
     pub(super) fn handle_create_string_table(
         &mut self,
         msg: protobuf::CsvcMsgCreateStringTable,
-    ) -> Result<(), std::io::Error> {
+    ) -> Result<(), ParserError> {
         let (
             Some(name),
             Some(entries),
@@ -323,8 +328,7 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
             msg.using_varint_bitcounts,
         )
         else {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
+            return Err(ParserError::invalid_data(
                 "Missing values in create string table message",
             ));
         };
@@ -364,10 +368,9 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
             &snap::raw::Decoder::new()
                 .decompress_vec(&string_data)
                 .map_err(|err| {
-                    std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Failed to decompress string table: {err:?}"),
-                    )
+                    ParserError::decompression(format!(
+                        "Failed to decompress string table: {err:?}"
+                    ))
                 })?
         } else {
             string_data.as_ref()
@@ -382,12 +385,11 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
     pub(super) fn handle_update_string_table(
         &mut self,
         _msg: protobuf::CsvcMsgUpdateStringTable,
-    ) -> Result<(), std::io::Error> {
+    ) -> Result<(), ParserError> {
         let (Some(table_id), Some(entries), Some(data)) =
             (_msg.table_id, _msg.num_changed_entries, _msg.string_data)
         else {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
+            return Err(ParserError::invalid_data(
                 "Missing values in update string table message",
             ));
         };
@@ -412,7 +414,7 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
     pub(super) fn handle_demo_string_tables(
         &mut self,
         msg: protobuf::CDemoStringTables,
-    ) -> Result<(), std::io::Error> {
+    ) -> Result<(), ParserError> {
         for table in msg.tables.into_iter() {
             let Some(name) = table.table_name else {
                 error!("Missing name in demo string table");
@@ -444,7 +446,7 @@ impl<T: std::io::BufRead + Send + Sync> CsDemoParser<T> {
 fn cache_user_info(
     key: &str,
     value: Option<&[u8]>,
-) -> Result<StringTableCache<protobuf::CMsgPlayerInfo>, std::io::Error> {
+) -> Result<StringTableCache<protobuf::CMsgPlayerInfo>, ParserError> {
     let Some(value) = value else {
         return Ok(StringTableCache::NotChanged);
     };
@@ -452,10 +454,7 @@ fn cache_user_info(
     let buf = Bytes::copy_from_slice(value);
     protobuf::CMsgPlayerInfo::decode(buf)
         .map_err(|err| {
-            std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!("Failed to decode user info for key {key}: {err:?}"),
-            )
+            ParserError::invalid_data(format!("Failed to decode user info for key {key}: {err:?}"))
         })
         .map(StringTableCache::Parsed)
 }